@@ -0,0 +1,62 @@
+//! parsing of the `#[asn1(...)]` attributes
+
+use syn::{Attribute, Expr, LitInt};
+
+/// Tagging of an element
+#[derive(Clone, Copy)]
+pub enum Tagging {
+    /// element encoded with its own universal tag
+    Universal,
+    /// context specific tag replacing the element tag
+    Implicit(u32),
+    /// context specific tag wrapping the element
+    Explicit(u32),
+}
+
+/// Attributes of a struct field or an enum variant
+pub struct Asn1Attrs {
+    pub tagging: Tagging,
+    pub optional: bool,
+    pub default: Option<Expr>,
+}
+
+impl Asn1Attrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut r = Asn1Attrs {
+            tagging: Tagging::Universal,
+            optional: false,
+            default: None,
+        };
+        for attr in attrs.iter().filter(|a| a.path().is_ident("asn1")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("implicit") || meta.path.is_ident("explicit") {
+                    if !matches!(r.tagging, Tagging::Universal) {
+                        return Err(meta.error("only one of implicit or explicit can be used"));
+                    }
+                    let tag = meta.value()?.parse::<LitInt>()?.base10_parse::<u32>()?;
+                    r.tagging = if meta.path.is_ident("implicit") {
+                        Tagging::Implicit(tag)
+                    } else {
+                        Tagging::Explicit(tag)
+                    };
+                    Ok(())
+                } else if meta.path.is_ident("optional") {
+                    r.optional = true;
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    r.default = Some(meta.value()?.parse::<Expr>()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown asn1 attribute"))
+                }
+            })?;
+            if r.optional && r.default.is_some() {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "optional and default cannot be used together",
+                ));
+            }
+        }
+        Ok(r)
+    }
+}
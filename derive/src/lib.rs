@@ -0,0 +1,382 @@
+//! Derive macros for the basn1 `DerDecode` and `DerEncode` traits
+//!
+//! * a struct is encoded as a SEQUENCE of its fields, in order
+//! * an enum is encoded as a CHOICE, where each variant is either a unit
+//!   variant (encoded as NULL) or has exactly one unnamed field. When decoding,
+//!   the variant is chosen from the identifier of the element
+//!
+//! Struct fields and enum variants accept the following attributes:
+//!
+//! * `#[asn1(implicit = N)]` : the element use the context specific IMPLICIT tag N
+//! * `#[asn1(explicit = N)]` : the element is wrapped in the context specific EXPLICIT tag N
+//! * `#[asn1(optional)]` : the element is OPTIONAL, and the field type is an `Option`
+//! * `#[asn1(default = expr)]` : the element has a DEFAULT value, which is not
+//!   encoded and is used when decoding and the element is not present. As in
+//!   DER, an element encoded with the DEFAULT value is rejected when decoding
+//!
+//! ```ignore
+//! #[derive(DerDecode, DerEncode)]
+//! struct Certificate<'a> {
+//!     #[asn1(explicit = 0, default = false)]
+//!     flag: bool,
+//!     serial: &'a Integer,
+//!     #[asn1(implicit = 1, optional)]
+//!     issuer_unique_id: Option<&'a BitString>,
+//! }
+//! ```
+
+extern crate proc_macro;
+
+mod attr;
+
+use attr::{Asn1Attrs, Tagging};
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, parse_quote, Data, DeriveInput, Fields, GenericParam, Generics, Ident,
+    Lifetime, LifetimeParam, Type,
+};
+
+#[proc_macro_derive(DerDecode, attributes(asn1))]
+pub fn derive_der_decode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_decode(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(DerEncode, attributes(asn1))]
+pub fn derive_der_encode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_encode(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// the element of a struct or an enum variant to decode or encode
+struct Element {
+    attrs: Asn1Attrs,
+    ty: Type,
+}
+
+fn struct_elements(fields: &Fields) -> syn::Result<Vec<Element>> {
+    fields
+        .iter()
+        .map(|f| {
+            Ok(Element {
+                attrs: Asn1Attrs::parse(&f.attrs)?,
+                ty: f.ty.clone(),
+            })
+        })
+        .collect()
+}
+
+/// return the element of every variant, and whether the variant is a unit variant
+fn enum_elements(data: &syn::DataEnum) -> syn::Result<Vec<(Ident, Element, bool)>> {
+    if data.variants.is_empty() {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "a CHOICE need at least one variant",
+        ));
+    }
+    data.variants
+        .iter()
+        .map(|v| {
+            let attrs = Asn1Attrs::parse(&v.attrs)?;
+            if attrs.optional || attrs.default.is_some() {
+                return Err(syn::Error::new_spanned(
+                    v,
+                    "optional and default are not supported on CHOICE variants",
+                ));
+            }
+            let (ty, unit) = match &v.fields {
+                Fields::Unit => (parse_quote!(()), true),
+                Fields::Unnamed(f) if f.unnamed.len() == 1 => (f.unnamed[0].ty.clone(), false),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        v,
+                        "CHOICE variants need to be unit or have exactly one unnamed field",
+                    ))
+                }
+            };
+            Ok((v.ident.clone(), Element { attrs, ty }, unit))
+        })
+        .collect()
+}
+
+// expression decoding one element of type ty from the reader, without the optional handling
+fn decode_one(reader: &TokenStream2, tagging: Tagging, ty: &TokenStream2) -> TokenStream2 {
+    match tagging {
+        Tagging::Universal => quote!(#reader.decode::<#ty>()),
+        Tagging::Implicit(n) => quote!(#reader.decode_implicit::<#ty>(#n)),
+        Tagging::Explicit(n) => quote!(#reader.decode_explicit::<#ty>(#n)),
+    }
+}
+
+// expression decoding one element of type ty from the reader if it is present, as an Option
+fn decode_optional(reader: &TokenStream2, tagging: Tagging, ty: &TokenStream2) -> TokenStream2 {
+    match tagging {
+        Tagging::Universal => quote!(#reader.optional::<#ty>()),
+        Tagging::Implicit(n) | Tagging::Explicit(n) => {
+            let r = quote!(__r);
+            let one = decode_one(&r, tagging, ty);
+            quote!(#reader.if_tag(::basn1::Class::Context, #n, |#r| #one))
+        }
+    }
+}
+
+// expression checking if the identifier is the one of an element of type ty with the tagging
+fn identifier_matches(
+    identifier: &TokenStream2,
+    tagging: Tagging,
+    ty: &TokenStream2,
+    lifetime: &Lifetime,
+) -> TokenStream2 {
+    match tagging {
+        Tagging::Universal => {
+            quote!(<#ty as ::basn1::der::DerDecode<#lifetime>>::can_decode(#identifier))
+        }
+        Tagging::Implicit(n) => quote! {
+            #identifier.class == ::basn1::Class::Context && #identifier.tag.value() == #n
+        },
+        Tagging::Explicit(n) => quote! {
+            #identifier.class == ::basn1::Class::Context
+                && #identifier.pc == ::basn1::PC::Constructed
+                && #identifier.tag.value() == #n
+        },
+    }
+}
+
+fn decode_element(reader: &TokenStream2, element: &Element) -> TokenStream2 {
+    let infer = quote!(_);
+    match (&element.attrs.default, element.attrs.optional) {
+        (Some(default), _) => {
+            let d = quote!(__d);
+            let opt = decode_optional(&d, element.attrs.tagging, &infer);
+            quote!(#reader.with_default(#default, |#d| #opt)?)
+        }
+        (None, true) => {
+            let opt = decode_optional(reader, element.attrs.tagging, &infer);
+            quote!(#opt?)
+        }
+        (None, false) => {
+            let ty = &element.ty;
+            let one = decode_one(reader, element.attrs.tagging, &quote!(#ty));
+            quote!(#one?)
+        }
+    }
+}
+
+// statement encoding the element value (a reference) to the writer, without the optional handling
+fn encode_one(writer: &TokenStream2, tagging: Tagging, value: &TokenStream2) -> TokenStream2 {
+    match tagging {
+        Tagging::Universal => quote!(#writer.encode(#value)?;),
+        Tagging::Implicit(n) => quote!(#writer.encode_implicit(#n, #value)?;),
+        Tagging::Explicit(n) => quote!(#writer.encode_explicit(#n, #value)?;),
+    }
+}
+
+fn encode_element(writer: &TokenStream2, element: &Element, value: TokenStream2) -> TokenStream2 {
+    let tagging = element.attrs.tagging;
+    match (&element.attrs.default, element.attrs.optional) {
+        (Some(default), _) => {
+            let one = encode_one(writer, tagging, &quote!(&#value));
+            quote!(if #value != #default { #one })
+        }
+        (None, true) => {
+            let one = encode_one(writer, tagging, &quote!(__v));
+            quote!(if let Some(__v) = &#value { #one })
+        }
+        (None, false) => encode_one(writer, tagging, &quote!(&#value)),
+    }
+}
+
+// add the trait bound to every type parameter
+fn add_bounds(generics: &mut Generics, bound: TokenStream2) {
+    let tys: Vec<Ident> = generics.type_params().map(|t| t.ident.clone()).collect();
+    let where_clause = generics.make_where_clause();
+    for ty in tys {
+        where_clause.predicates.push(parse_quote!(#ty: #bound));
+    }
+}
+
+fn expand_decode(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    // the decoding lifetime is the type lifetime if any, otherwise a new one
+    let mut generics = input.generics.clone();
+    let lifetime = match generics.lifetimes().next() {
+        Some(l) => l.lifetime.clone(),
+        None => {
+            let l = Lifetime::new("'__asn1", Span::call_site());
+            generics
+                .params
+                .insert(0, GenericParam::Lifetime(LifetimeParam::new(l.clone())));
+            l
+        }
+    };
+    add_bounds(&mut generics, quote!(::basn1::der::DerDecode<#lifetime>));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let reader = quote!(reader);
+    let identifier = quote!(identifier);
+    let (body, can_decode) = match &input.data {
+        Data::Struct(data) => {
+            let elements = struct_elements(&data.fields)?;
+            let seq = quote!(__seq);
+            let vars: Vec<Ident> = (0..elements.len())
+                .map(|i| format_ident!("__field{}", i))
+                .collect();
+            let decodes = elements.iter().zip(vars.iter()).map(|(e, var)| {
+                let ty = &e.ty;
+                let expr = decode_element(&seq, e);
+                quote!(let #var: #ty = #expr;)
+            });
+            let construct = match &data.fields {
+                Fields::Named(fields) => {
+                    let names = fields.named.iter().map(|f| f.ident.as_ref().unwrap());
+                    quote!(#name { #(#names: #vars),* })
+                }
+                Fields::Unnamed(_) => quote!(#name ( #(#vars),* )),
+                Fields::Unit => quote!(#name),
+            };
+            let body = quote! {
+                let mut #seq = #reader.sequence()?;
+                #(#decodes)*
+                #seq.done()?;
+                Ok(#construct)
+            };
+            // universal constructed SEQUENCE
+            let can_decode = quote! {
+                #identifier.class == ::basn1::Class::Universal
+                    && #identifier.pc == ::basn1::PC::Constructed
+                    && #identifier.tag.value() == 0x10
+            };
+            (body, can_decode)
+        }
+        Data::Enum(data) => {
+            let elements = enum_elements(data)?;
+            let r = quote!(__r);
+            let alternatives = elements.iter().map(|(variant, e, unit)| {
+                let ty = &e.ty;
+                let cond =
+                    identifier_matches(&identifier, e.attrs.tagging, &quote!(#ty), &lifetime);
+                let one = decode_one(&r, e.attrs.tagging, &quote!(#ty));
+                let value = if *unit {
+                    quote!(#one.map(|_| #name::#variant))
+                } else {
+                    quote!(#one.map(#name::#variant))
+                };
+                quote! {
+                    if #cond {
+                        return Some(#value);
+                    }
+                }
+            });
+            let body = quote! {
+                #reader.choice(|#r, #identifier| {
+                    #(#alternatives)*
+                    None
+                })
+            };
+            let matches = elements.iter().map(|(_, e, _)| {
+                let ty = &e.ty;
+                identifier_matches(&identifier, e.attrs.tagging, &quote!(#ty), &lifetime)
+            });
+            let can_decode = quote!(#((#matches))||*);
+            (body, can_decode)
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "DerDecode cannot be derived for unions",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::basn1::der::DerDecode<#lifetime> for #name #ty_generics #where_clause {
+            fn can_decode(#identifier: &::basn1::Identifier) -> bool {
+                #can_decode
+            }
+
+            fn decode(
+                #reader: &mut ::basn1::der::Reader<#lifetime>,
+            ) -> ::core::result::Result<Self, ::basn1::der::reader::Error> {
+                #body
+            }
+        }
+    })
+}
+
+fn expand_encode(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let mut generics = input.generics.clone();
+    add_bounds(&mut generics, quote!(::basn1::der::DerEncode));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let writer = quote!(writer);
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let elements = struct_elements(&data.fields)?;
+            let w = quote!(__w);
+            let encodes = elements
+                .iter()
+                .zip(data.fields.iter())
+                .enumerate()
+                .map(|(i, (e, f))| {
+                    let value = match &f.ident {
+                        Some(ident) => quote!(self.#ident),
+                        None => {
+                            let index = syn::Index::from(i);
+                            quote!(self.#index)
+                        }
+                    };
+                    encode_element(&w, e, value)
+                });
+            quote! {
+                #writer.sequence(|#w| {
+                    #(#encodes)*
+                    Ok(())
+                })
+            }
+        }
+        Data::Enum(data) => {
+            let elements = enum_elements(data)?;
+            let arms = elements.iter().map(|(variant, e, unit)| {
+                if *unit {
+                    let one = encode_one(&writer, e.attrs.tagging, &quote!(&()));
+                    quote!(#name::#variant => { #one })
+                } else {
+                    let one = encode_one(&writer, e.attrs.tagging, &quote!(__v));
+                    quote!(#name::#variant(__v) => { #one })
+                }
+            });
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+                Ok(())
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "DerEncode cannot be derived for unions",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::basn1::der::DerEncode for #name #ty_generics #where_clause {
+            fn encode(
+                &self,
+                #writer: &mut ::basn1::der::Writer<'_>,
+            ) -> ::core::result::Result<(), ::basn1::der::writer::Error> {
+                #body
+            }
+        }
+    })
+}
@@ -0,0 +1,12 @@
+//! Basic Encoding Rules (BER) Reader
+//!
+//! This encoding is the most relaxed of the ASN.1 binary encodings, and allow
+//! multiple representations of the same value: indefinite lengths terminated by
+//! end-of-contents markers, strings split in constructed segments, non minimal
+//! integer and length encodings, and any non zero byte as a true boolean.
+//!
+//! This is mostly useful to read material produced by older or non strict
+//! encoders, for anything else the DER reader should be preferred.
+pub mod reader;
+
+pub use self::reader::{BitStringSegments, Reader, Segments, Utf8StringSegments};
@@ -0,0 +1,914 @@
+use crate::header::constants;
+use crate::header::{Class, Identifier, IdentifierDecodeError, Length, LengthDecodeError, PC};
+use crate::intenc::{strip_redundant_sign_bytes, Integer8Bit};
+use crate::objects::*;
+use core::fmt;
+
+/// Maximum nesting of constructed segments in a constructed string
+const MAX_SEGMENT_DEPTH: usize = 16;
+
+/// Maximum nesting of indefinite length elements
+///
+/// Every indefinite element is scanned to find its end-of-contents, including
+/// by the scan of its parent, so the reading cost is bounded by this depth
+/// times the size of the input.
+const MAX_INDEFINITE_DEPTH: usize = 32;
+
+/// ASN.1 BER Reader on slice
+#[derive(Clone)]
+pub struct Reader<'a> {
+    index: usize,
+    slice: &'a [u8],
+    // offset of the slice relative to the start of the top level stream,
+    // used to report absolute positions in errors
+    offset: usize,
+}
+
+/// BER ASN.1 Error when reading data
+#[derive(Debug, Clone)]
+pub enum Error {
+    ExpectedCType {
+        expected: PC,
+        got: PC,
+    },
+    ExpectedTag {
+        expected: u32,
+        got: u32,
+    },
+    ExpectedClass {
+        expected: Class,
+        got: Class,
+    },
+    /// The identifier starting at offset is missing or incomplete
+    IdentifierTruncated {
+        offset: usize,
+    },
+    /// The identifier starting at offset is not a valid encoding
+    IdentifierInvalid {
+        offset: usize,
+    },
+    /// The length starting at offset is missing or incomplete
+    LengthTruncated {
+        offset: usize,
+    },
+    /// The length starting at offset is not a valid encoding
+    LengthInvalid {
+        offset: usize,
+    },
+    /// The content starting at offset needs more bytes than remaining in the stream
+    DataTruncated {
+        offset: usize,
+        length: usize,
+        remaining: usize,
+    },
+    /// The primitive element with content starting at offset use the indefinite length
+    IndefiniteLengthPrimitive {
+        offset: usize,
+    },
+    /// The indefinite length content starting at offset is not terminated
+    EndOfContentsMissing {
+        offset: usize,
+    },
+    /// The segment of a constructed string starting at offset is not valid
+    SegmentInvalid {
+        offset: usize,
+    },
+    /// The constructed string segments starting at offset are nested too deeply
+    SegmentNestingTooDeep {
+        offset: usize,
+    },
+    /// The indefinite length elements starting at offset are nested too deeply
+    IndefiniteNestingTooDeep {
+        offset: usize,
+    },
+    BoolLengthInvalid(usize),
+    BitStringEncodingEmpty,
+    BitStringEncodingInvalidStart,
+    IntegerEmpty,
+    Utf8Invalid,
+    NullEncodingInvalid,
+    OIDInvalid,
+    ReaderNotTerminated {
+        index: usize,
+        len: usize,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ExpectedCType { expected, got } => {
+                write!(f, "expected {:?} element, got {:?}", expected, got)
+            }
+            Error::ExpectedTag { expected, got } => {
+                write!(f, "expected tag {}, got tag {}", expected, got)
+            }
+            Error::ExpectedClass { expected, got } => {
+                write!(f, "expected {:?} class, got {:?} class", expected, got)
+            }
+            Error::IdentifierTruncated { offset } => {
+                write!(f, "identifier truncated at offset {}", offset)
+            }
+            Error::IdentifierInvalid { offset } => {
+                write!(f, "invalid identifier at offset {}", offset)
+            }
+            Error::LengthTruncated { offset } => write!(f, "length truncated at offset {}", offset),
+            Error::LengthInvalid { offset } => write!(f, "invalid length at offset {}", offset),
+            Error::DataTruncated {
+                offset,
+                length,
+                remaining,
+            } => write!(
+                f,
+                "content at offset {} needs {} bytes but only {} remaining",
+                offset, length, remaining
+            ),
+            Error::IndefiniteLengthPrimitive { offset } => {
+                write!(
+                    f,
+                    "primitive element with indefinite length at offset {}",
+                    offset
+                )
+            }
+            Error::EndOfContentsMissing { offset } => {
+                write!(
+                    f,
+                    "missing end-of-contents for content at offset {}",
+                    offset
+                )
+            }
+            Error::SegmentInvalid { offset } => {
+                write!(f, "invalid string segment at offset {}", offset)
+            }
+            Error::SegmentNestingTooDeep { offset } => {
+                write!(f, "string segments nested too deeply at offset {}", offset)
+            }
+            Error::IndefiniteNestingTooDeep { offset } => {
+                write!(
+                    f,
+                    "indefinite length elements nested too deeply at offset {}",
+                    offset
+                )
+            }
+            Error::BoolLengthInvalid(len) => write!(f, "invalid BOOLEAN length {}", len),
+            Error::BitStringEncodingEmpty => write!(f, "empty BIT STRING encoding"),
+            Error::BitStringEncodingInvalidStart => write!(f, "invalid BIT STRING unused bits"),
+            Error::IntegerEmpty => write!(f, "empty INTEGER encoding"),
+            Error::Utf8Invalid => write!(f, "invalid UTF8String"),
+            Error::NullEncodingInvalid => write!(f, "invalid NULL encoding"),
+            Error::OIDInvalid => write!(f, "invalid OBJECT IDENTIFIER"),
+            Error::ReaderNotTerminated { index, len } => {
+                write!(f, "reader not terminated at index {} of {}", index, len)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+fn assume_tag(header: &Identifier, tag: u32) -> Result<(), Error> {
+    if header.class != Class::Universal {
+        return Err(Error::ExpectedClass {
+            expected: Class::Universal,
+            got: header.class,
+        });
+    }
+    if header.tag.value() != tag {
+        return Err(Error::ExpectedTag {
+            expected: tag,
+            got: header.tag.value(),
+        });
+    }
+    Ok(())
+}
+
+fn assume(header: &Identifier, pc: PC, tag: u32) -> Result<(), Error> {
+    assume_tag(header, tag)?;
+    if header.pc != pc {
+        return Err(Error::ExpectedCType {
+            expected: pc,
+            got: header.pc,
+        });
+    }
+    Ok(())
+}
+
+fn is_end_of_contents(header: &Identifier, length: Length) -> bool {
+    header.class == Class::Universal
+        && header.pc == PC::Primitive
+        && header.tag.value() == constants::TAG_EOC
+        && length == Length::Short(0)
+}
+
+// decode the identifier and length at the start of the slice, and return the
+// size of the header. offset is the absolute position of slice for error reporting
+fn decode_header(slice: &[u8], offset: usize) -> Result<(Identifier, Length, usize), Error> {
+    let (hdr, hdr_sz) = Identifier::decode(slice).map_err(|e| match e {
+        IdentifierDecodeError::EmptyHeader | IdentifierDecodeError::TagEncodingIncomplete => {
+            Error::IdentifierTruncated { offset }
+        }
+        IdentifierDecodeError::TagEncodingOverflow
+        | IdentifierDecodeError::TagEncodingNonCanonical => Error::IdentifierInvalid { offset },
+    })?;
+    let (len, len_sz) = Length::decode(&slice[hdr_sz..]).map_err(|e| {
+        let offset = offset + hdr_sz;
+        match e {
+            LengthDecodeError::EncodingIncomplete => Error::LengthTruncated { offset },
+            _ => Error::LengthInvalid { offset },
+        }
+    })?;
+    Ok((hdr, len, hdr_sz + len_sz))
+}
+
+// find the end-of-contents marker terminating the indefinite content starting
+// at the beginning of slice, and return its position.
+//
+// This doesn't recurse into the nested elements, but keep track of the number of
+// opened indefinite encodings, since their content need to be scanned to find
+// their own end-of-contents marker.
+fn find_end_of_contents(slice: &[u8], offset: usize) -> Result<usize, Error> {
+    let mut depth = 1usize;
+    let mut pos = 0;
+    loop {
+        if pos == slice.len() {
+            return Err(Error::EndOfContentsMissing { offset });
+        }
+        let (hdr, len, sz) = decode_header(&slice[pos..], offset + pos)?;
+        if is_end_of_contents(&hdr, len) {
+            depth -= 1;
+            if depth == 0 {
+                return Ok(pos);
+            }
+            pos += sz;
+            continue;
+        }
+        match len.value() {
+            None => {
+                if hdr.pc == PC::Primitive {
+                    return Err(Error::IndefiniteLengthPrimitive {
+                        offset: offset + pos + sz,
+                    });
+                }
+                depth += 1;
+                if depth > MAX_INDEFINITE_DEPTH {
+                    return Err(Error::IndefiniteNestingTooDeep {
+                        offset: offset + pos,
+                    });
+                }
+                pos += sz;
+            }
+            Some(v) => {
+                let v = v as usize;
+                let remaining = slice.len() - pos - sz;
+                if v > remaining {
+                    return Err(Error::DataTruncated {
+                        offset: offset + pos + sz,
+                        length: v,
+                        remaining,
+                    });
+                }
+                pos += sz + v;
+            }
+        }
+    }
+}
+
+// get the content of an element, the slice starting just after the element header.
+//
+// return the content and the number of bytes consumed by the content, which for
+// indefinite length include the end-of-contents marker
+fn element_content(
+    slice: &[u8],
+    offset: usize,
+    pc: PC,
+    length: Length,
+) -> Result<(&[u8], usize), Error> {
+    match length.value() {
+        None => {
+            if pc == PC::Primitive {
+                return Err(Error::IndefiniteLengthPrimitive { offset });
+            }
+            let end = find_end_of_contents(slice, offset)?;
+            // end-of-contents is always encoded on 2 bytes
+            Ok((&slice[0..end], end + 2))
+        }
+        Some(v) => {
+            let v = v as usize;
+            if v > slice.len() {
+                return Err(Error::DataTruncated {
+                    offset,
+                    length: v,
+                    remaining: slice.len(),
+                });
+            }
+            Ok((&slice[0..v], v))
+        }
+    }
+}
+
+// verify that the content of a constructed string is only made of segments of the
+// same tag, and call f on every primitive segment, in order
+fn validate_segments<'a, F>(
+    content: &'a [u8],
+    offset: usize,
+    tag: u32,
+    depth: usize,
+    f: &mut F,
+) -> Result<(), Error>
+where
+    F: FnMut(&'a [u8], usize) -> Result<(), Error>,
+{
+    if depth > MAX_SEGMENT_DEPTH {
+        return Err(Error::SegmentNestingTooDeep { offset });
+    }
+    let mut pos = 0;
+    while pos < content.len() {
+        let (hdr, len, sz) = decode_header(&content[pos..], offset + pos)?;
+        if hdr.class != Class::Universal || hdr.tag.value() != tag {
+            return Err(Error::SegmentInvalid {
+                offset: offset + pos,
+            });
+        }
+        let sub_offset = offset + pos + sz;
+        let (sub, consumed) = element_content(&content[pos + sz..], sub_offset, hdr.pc, len)?;
+        match hdr.pc {
+            PC::Primitive => f(sub, sub_offset)?,
+            PC::Constructed => validate_segments(sub, sub_offset, tag, depth + 1, f)?,
+        }
+        pos += sz + consumed;
+    }
+    Ok(())
+}
+
+/// Incremental UTF8 validation of data split in multiple chunks,
+/// where a character can be split between 2 chunks
+struct Utf8Validator {
+    pending: [u8; 4],
+    nb_pending: usize,
+}
+
+impl Utf8Validator {
+    fn new() -> Self {
+        Utf8Validator {
+            pending: [0; 4],
+            nb_pending: 0,
+        }
+    }
+
+    fn feed(&mut self, mut chunk: &[u8]) -> Result<(), Error> {
+        if self.nb_pending > 0 {
+            let width = match self.pending[0] {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                _ => 4,
+            };
+            while self.nb_pending < width && !chunk.is_empty() {
+                self.pending[self.nb_pending] = chunk[0];
+                self.nb_pending += 1;
+                chunk = &chunk[1..];
+            }
+            if self.nb_pending < width {
+                return Ok(());
+            }
+            core::str::from_utf8(&self.pending[0..width]).map_err(|_| Error::Utf8Invalid)?;
+            self.nb_pending = 0;
+        }
+        match core::str::from_utf8(chunk) {
+            Ok(_) => Ok(()),
+            Err(e) if e.error_len().is_none() => {
+                // the chunk ends with an incomplete character
+                let rem = &chunk[e.valid_up_to()..];
+                self.pending[0..rem.len()].copy_from_slice(rem);
+                self.nb_pending = rem.len();
+                Ok(())
+            }
+            Err(_) => Err(Error::Utf8Invalid),
+        }
+    }
+
+    fn finish(&self) -> Result<(), Error> {
+        if self.nb_pending > 0 {
+            Err(Error::Utf8Invalid)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Iterator over the data segments of a BER string
+///
+/// A primitive string is made of only one segment, whereas a constructed
+/// string is made of any number of segments (possibly empty)
+#[derive(Debug, Clone)]
+pub struct Segments<'a> {
+    content: &'a [u8],
+    constructed: bool,
+    index: usize,
+}
+
+impl<'a> Segments<'a> {
+    fn new(content: &'a [u8], pc: PC) -> Self {
+        Segments {
+            content,
+            constructed: pc == PC::Constructed,
+            index: 0,
+        }
+    }
+
+    /// Return the remaining data as one slice, if it is not split in multiple segments
+    pub fn as_slice(&self) -> Option<&'a [u8]> {
+        let mut segments = self.clone();
+        match segments.next() {
+            None => Some(&[]),
+            Some(s) => match segments.next() {
+                None => Some(s),
+                Some(_) => None,
+            },
+        }
+    }
+
+    /// Return the total number of bytes in the remaining segments
+    pub fn total_len(&self) -> usize {
+        self.clone().map(|s| s.len()).sum()
+    }
+
+    /// Copy all the remaining segments contiguously in the out buffer,
+    /// and return the written sub-slice
+    ///
+    /// If the out buffer is too small, nothing is returned
+    pub fn copy_to_slice<'b>(&self, out: &'b mut [u8]) -> Option<&'b [u8]> {
+        let mut index = 0;
+        for s in self.clone() {
+            if index + s.len() > out.len() {
+                return None;
+            }
+            out[index..index + s.len()].copy_from_slice(s);
+            index += s.len();
+        }
+        Some(&out[0..index])
+    }
+}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.constructed {
+            if self.index < self.content.len() {
+                self.index = self.content.len();
+                return Some(self.content);
+            }
+            return None;
+        }
+        // the segments have been validated when read, so it's possible
+        // to just scan linearly over the nested headers, skipping
+        // over the constructed headers and end-of-contents markers
+        while self.index < self.content.len() {
+            let (hdr, len, sz) = decode_header(&self.content[self.index..], 0).ok()?;
+            self.index += sz;
+            if hdr.pc == PC::Constructed || is_end_of_contents(&hdr, len) {
+                continue;
+            }
+            let start = self.index;
+            self.index += len.value()? as usize;
+            if self.index > start {
+                return Some(&self.content[start..self.index]);
+            }
+        }
+        None
+    }
+}
+
+/// Iterator over the segments of a BER BitString
+///
+/// All the segments are full bytes, except possibly the last one
+#[derive(Debug, Clone)]
+pub struct BitStringSegments<'a>(Segments<'a>);
+
+impl<'a> BitStringSegments<'a> {
+    /// Return the bitstring if it is not split in multiple segments
+    ///
+    /// A constructed bitstring without any segment is the empty bitstring
+    pub fn as_bitstring(&self) -> Option<&'a BitString> {
+        self.0.as_slice().map(|s| match s {
+            [] => BitString::from_raw_slice(&[0]),
+            s => BitString::from_raw_slice(s),
+        })
+    }
+
+    /// Return the total number of bits of the remaining segments
+    pub fn bits(&self) -> usize {
+        self.clone().map(|b| b.bits()).sum()
+    }
+}
+
+impl<'a> Iterator for BitStringSegments<'a> {
+    type Item = &'a BitString;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(BitString::from_raw_slice)
+    }
+}
+
+/// Iterator over the segments of a BER UTF8 String
+///
+/// The full string is valid UTF8, but a character can be split between
+/// two segments, so each segment is only given as bytes
+#[derive(Debug, Clone)]
+pub struct Utf8StringSegments<'a>(Segments<'a>);
+
+impl<'a> Utf8StringSegments<'a> {
+    /// Return the string if it is not split in multiple segments
+    pub fn as_str(&self) -> Option<&'a str> {
+        self.0
+            .as_slice()
+            .map(|s| core::str::from_utf8(s).expect("validated utf8"))
+    }
+
+    /// Copy all the remaining segments contiguously in the out buffer,
+    /// and return the written string
+    ///
+    /// If the out buffer is too small, nothing is returned
+    pub fn copy_to_str<'b>(&self, out: &'b mut [u8]) -> Option<&'b str> {
+        self.0
+            .copy_to_slice(out)
+            .map(|s| core::str::from_utf8(s).expect("validated utf8"))
+    }
+}
+
+impl<'a> Iterator for Utf8StringSegments<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+/// Iterator to iterate over an element from a BER SET
+#[derive(Clone)]
+pub struct Set<'a, F>(Reader<'a>, F);
+
+impl<'a, A, F> Iterator for Set<'a, F>
+where
+    F: Fn(&mut Reader<'a>) -> Result<A, Error>,
+{
+    type Item = Result<A, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0.index < self.0.slice.len() {
+            Some(self.1(&mut self.0))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> Reader<'a> {
+    /// Create a new BER Reader where the read buffer is given by the user
+    pub fn new(slice: &'a [u8]) -> Self {
+        Reader {
+            slice,
+            index: 0,
+            offset: 0,
+        }
+    }
+
+    /// absolute offset of the current position in the top level stream
+    fn position(&self) -> usize {
+        self.offset + self.index
+    }
+
+    fn next(&mut self) -> Result<(Identifier, Length), Error> {
+        let (hdr, len, sz) = decode_header(&self.slice[self.index..], self.position())?;
+        self.index += sz;
+        Ok((hdr, len))
+    }
+
+    fn next_assume(&mut self, pc: PC, tag: u32) -> Result<Length, Error> {
+        let (hdr, len) = self.next()?;
+        assume(&hdr, pc, tag)?;
+        Ok(len)
+    }
+
+    fn content(&mut self, pc: PC, length: Length) -> Result<&'a [u8], Error> {
+        let (sub, consumed) =
+            element_content(&self.slice[self.index..], self.position(), pc, length)?;
+        self.index += consumed;
+        Ok(sub)
+    }
+
+    fn content_reader(&mut self, length: Length) -> Result<Reader<'a>, Error> {
+        let offset = self.position();
+        let slice = self.content(PC::Constructed, length)?;
+        Ok(Reader {
+            slice,
+            index: 0,
+            offset,
+        })
+    }
+
+    fn segments<F>(&mut self, tag: u32, mut f: F) -> Result<Segments<'a>, Error>
+    where
+        F: FnMut(&'a [u8], usize) -> Result<(), Error>,
+    {
+        let (hdr, len) = self.next()?;
+        assume_tag(&hdr, tag)?;
+        let offset = self.position();
+        let content = self.content(hdr.pc, len)?;
+        match hdr.pc {
+            PC::Primitive => f(content, offset)?,
+            PC::Constructed => validate_segments(content, offset, tag, 0, &mut f)?,
+        }
+        Ok(Segments::new(content, hdr.pc))
+    }
+
+    /// Get the next Tag / Length / Value triple, whichever it is
+    ///
+    /// For indefinite length, the value doesn't include the end-of-contents marker
+    pub fn anything(&mut self) -> Result<(Identifier, Length, &'a [u8]), Error> {
+        let (identifier, length) = self.next()?;
+        let slice = self.content(identifier.pc, length)?;
+        Ok((identifier, length, slice))
+    }
+
+    /// Get the next boolean from the stream, any non zero value being true
+    pub fn bool(&mut self) -> Result<bool, Error> {
+        let len = self.next_assume(PC::Primitive, constants::TAG_BOOLEAN)?;
+        let sub = self.content(PC::Primitive, len)?;
+        if sub.len() == 1 {
+            Ok(sub[0] != 0)
+        } else {
+            Err(Error::BoolLengthInvalid(sub.len()))
+        }
+    }
+
+    fn integer8bit(&mut self, tag: u32) -> Result<&'a Integer8Bit, Error> {
+        let len = self.next_assume(PC::Primitive, tag)?;
+        let sub = self.content(PC::Primitive, len)?;
+        if sub.is_empty() {
+            return Err(Error::IntegerEmpty);
+        }
+        Ok(Integer8Bit::unverified_from_slice(
+            strip_redundant_sign_bytes(sub),
+        ))
+    }
+
+    /// Get the next integer from the stream
+    ///
+    /// Non minimal encodings are accepted, and the integer returned
+    /// is the sub-slice with the redundant leading bytes removed
+    pub fn integer(&mut self) -> Result<&'a Integer, Error> {
+        self.integer8bit(constants::TAG_INTEGER)
+            .map(Integer::from_inner_slice)
+    }
+
+    /// Get the next enumerated from the stream
+    ///
+    /// Non minimal encodings are accepted, and the enumerated returned
+    /// is the sub-slice with the redundant leading bytes removed
+    pub fn enumerated(&mut self) -> Result<&'a Enumerated, Error> {
+        self.integer8bit(constants::TAG_ENUMERATED)
+            .map(Enumerated::from_inner_slice)
+    }
+
+    /// Get the next bitstring from the stream, either primitive or constructed
+    pub fn bitstring(&mut self) -> Result<BitStringSegments<'a>, Error> {
+        let mut last_unused = 0;
+        let segments = self.segments(constants::TAG_BIT_STRING, |sub, offset| {
+            if sub.is_empty() {
+                return Err(Error::BitStringEncodingEmpty);
+            }
+            // only the last segment can have unused bits
+            if last_unused > 0 {
+                return Err(Error::SegmentInvalid { offset });
+            }
+            let bit_unused = sub[0];
+            if bit_unused > 7 || (bit_unused > 0 && sub.len() == 1) {
+                return Err(Error::BitStringEncodingInvalidStart);
+            }
+            last_unused = bit_unused;
+            Ok(())
+        })?;
+        Ok(BitStringSegments(segments))
+    }
+
+    /// Get the next octetstring from the stream, either primitive or constructed
+    pub fn octetstring(&mut self) -> Result<Segments<'a>, Error> {
+        self.segments(constants::TAG_OCTET_STRING, |_, _| Ok(()))
+    }
+
+    /// Get the next utf8 string from the stream, either primitive or constructed
+    pub fn utf8_string(&mut self) -> Result<Utf8StringSegments<'a>, Error> {
+        let mut validator = Utf8Validator::new();
+        let segments = self.segments(constants::TAG_UTF8_STRING, |sub, _| validator.feed(sub))?;
+        validator.finish()?;
+        Ok(Utf8StringSegments(segments))
+    }
+
+    /// Get the next null from the stream
+    pub fn null(&mut self) -> Result<(), Error> {
+        let len = self.next_assume(PC::Primitive, constants::TAG_NULL)?;
+        let sub = self.content(PC::Primitive, len)?;
+        if !sub.is_empty() {
+            return Err(Error::NullEncodingInvalid);
+        }
+        Ok(())
+    }
+
+    /// Get the next OID from the stream
+    pub fn oid(&mut self) -> Result<&'a OID, Error> {
+        let len = self.next_assume(PC::Primitive, constants::TAG_OID)?;
+        let sub = self.content(PC::Primitive, len)?;
+        OID::parse_from_slice(sub).map_err(|_| Error::OIDInvalid)
+    }
+
+    /// Get the next sequence from the stream as a Reader
+    pub fn sequence(&mut self) -> Result<Reader<'a>, Error> {
+        let len = self.next_assume(PC::Constructed, constants::TAG_SEQUENCE)?;
+        self.content_reader(len)
+    }
+
+    /// Get the next set from the stream as a Set iterator
+    pub fn set<A, F>(&mut self, f: F) -> Result<Set<'a, F>, Error>
+    where
+        F: Fn(&mut Reader<'a>) -> Result<A, Error>,
+    {
+        let len = self.next_assume(PC::Constructed, constants::TAG_SET)?;
+        let subreader = self.content_reader(len)?;
+        Ok(Set(subreader, f))
+    }
+
+    /// Check is the stream is done
+    ///
+    /// If the reader is not finished, then the Error::ReaderNotTerminated is returned
+    pub fn done(&self) -> Result<(), Error> {
+        if self.index == self.slice.len() {
+            Ok(())
+        } else {
+            Err(Error::ReaderNotTerminated {
+                index: self.index,
+                len: self.slice.len(),
+            })
+        }
+    }
+
+    /// Get the position of the reader in the slice
+    pub fn current_position(&self) -> usize {
+        self.index
+    }
+
+    /// Get the remaining buffer as a slice
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.slice[self.index..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    pub fn decode_relaxed_primitives() {
+        let data =
+            b"\x01\x01\x01\x02\x03\x00\x00\x05\x04\x81\x02\x01\x02\x02\x02\xff\x80\x05\x81\x00";
+        let mut reader = Reader::new(&data[..]);
+        assert!(reader.bool().expect("bool"));
+        let integer = reader.integer().expect("integer");
+        assert_eq!(integer.as_ref(), &[0x05]);
+        let octets = reader.octetstring().expect("octetstring");
+        assert_eq!(octets.as_slice(), Some(&[1u8, 2][..]));
+        let integer = reader.integer().expect("negative integer");
+        assert_eq!(integer.as_ref(), &[0x80]);
+        reader.null().expect("null");
+        reader.done().expect("done");
+    }
+
+    #[test]
+    pub fn decode_indefinite_sequence() {
+        // sequence { sequence { bool }, null } with indefinite lengths
+        let data = b"\x30\x80\x30\x80\x01\x01\xff\x00\x00\x05\x00\x00\x00\x02\x01\x07";
+        let mut reader = Reader::new(&data[..]);
+        let mut seq = reader.sequence().expect("outer sequence");
+        let mut inner = seq.sequence().expect("inner sequence");
+        assert!(inner.bool().expect("bool"));
+        inner.done().expect("inner done");
+        seq.null().expect("null");
+        seq.done().expect("outer done");
+        assert_eq!(reader.integer().expect("integer").as_ref(), &[7]);
+        reader.done().expect("done");
+    }
+
+    #[test]
+    pub fn decode_constructed_octetstring() {
+        // indefinite constructed octetstring, containing a primitive segment
+        // and a definite constructed with 2 segments
+        let data = b"\x24\x80\x04\x02\x01\x02\x24\x07\x04\x01\x03\x04\x02\x04\x05\x00\x00";
+        let mut reader = Reader::new(&data[..]);
+        let octets = reader.octetstring().expect("octetstring");
+        reader.done().expect("done");
+        assert_eq!(octets.as_slice(), None);
+        assert_eq!(octets.total_len(), 5);
+        let chunks: Vec<&[u8]> = octets.clone().collect();
+        assert_eq!(chunks, vec![&[1u8, 2][..], &[3][..], &[4, 5][..]]);
+        let mut out = [0u8; 5];
+        assert_eq!(octets.copy_to_slice(&mut out), Some(&[1u8, 2, 3, 4, 5][..]));
+        assert_eq!(octets.copy_to_slice(&mut out[0..4]), None);
+    }
+
+    #[test]
+    pub fn decode_constructed_bitstring() {
+        let data = b"\x23\x09\x03\x02\x00\xff\x03\x03\x04\xa5\xf0";
+        let mut reader = Reader::new(&data[..]);
+        let bits = reader.bitstring().expect("bitstring");
+        assert_eq!(bits.bits(), 8 + 12);
+        assert!(bits.as_bitstring().is_none());
+
+        // no segment at all
+        let mut reader = Reader::new(&b"\x23\x00"[..]);
+        let bits = reader.bitstring().expect("empty bitstring");
+        assert_eq!(bits.bits(), 0);
+        let bitstring = bits.as_bitstring().expect("one segment");
+        assert_eq!((bitstring.bits(), bitstring.bits_unused()), (0, 0));
+        assert!(bitstring.data_bytes().is_empty());
+
+        // unused bits in a segment that is not the last one
+        let data = b"\x23\x09\x03\x02\x04\xf0\x03\x03\x00\xa5\xf0";
+        let mut reader = Reader::new(&data[..]);
+        match reader.bitstring() {
+            Err(Error::SegmentInvalid { offset: 8 }) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+    }
+
+    #[test]
+    pub fn decode_constructed_utf8_string() {
+        // "été" with the first character split between 2 segments
+        let data = b"\x2c\x80\x0c\x01\xc3\x0c\x04\xa9t\xc3\xa9\x00\x00";
+        let mut reader = Reader::new(&data[..]);
+        let s = reader.utf8_string().expect("utf8 string");
+        assert_eq!(s.as_str(), None);
+        let mut out = [0u8; 16];
+        assert_eq!(s.copy_to_str(&mut out), Some("été"));
+
+        // incomplete character at the end
+        let data = b"\x2c\x80\x0c\x01\xc3\x00\x00";
+        let mut reader = Reader::new(&data[..]);
+        match reader.utf8_string() {
+            Err(Error::Utf8Invalid) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+    }
+
+    #[test]
+    pub fn decode_invalid() {
+        // missing end-of-contents
+        let mut reader = Reader::new(&b"\x30\x80\x05\x00"[..]);
+        match reader.sequence() {
+            Err(Error::EndOfContentsMissing { offset: 2 }) => {}
+            r => panic!("unexpected {:?}", r.map(|_| ())),
+        }
+
+        // indefinite length on a primitive
+        let mut reader = Reader::new(&b"\x04\x80\x01\x00\x00"[..]);
+        match reader.octetstring() {
+            Err(Error::IndefiniteLengthPrimitive { offset: 2 }) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+
+        // segment of the wrong type
+        let mut reader = Reader::new(&b"\x24\x03\x01\x01\xff"[..]);
+        match reader.octetstring() {
+            Err(Error::SegmentInvalid { offset: 2 }) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+
+        // too many nested indefinite sequences
+        let mut data = Vec::new();
+        for _ in 0..=MAX_INDEFINITE_DEPTH {
+            data.extend_from_slice(b"\x30\x80");
+        }
+        for _ in 0..=MAX_INDEFINITE_DEPTH {
+            data.extend_from_slice(b"\x00\x00");
+        }
+        assert!(Reader::new(&data[2..data.len() - 2]).sequence().is_ok());
+        match Reader::new(&data).sequence() {
+            Err(Error::IndefiniteNestingTooDeep { offset }) => {
+                assert_eq!(offset, 2 * MAX_INDEFINITE_DEPTH)
+            }
+            r => panic!("unexpected {:?}", r.map(|_| ())),
+        }
+
+        // truncated definite content
+        let mut reader = Reader::new(&b"\x30\x80\x04\x05\x00\x00\x00"[..]);
+        match reader.sequence() {
+            Err(Error::DataTruncated {
+                offset: 4,
+                length: 5,
+                remaining: 3,
+            }) => {}
+            r => panic!("unexpected {:?}", r.map(|_| ())),
+        }
+    }
+}
@@ -0,0 +1,10 @@
+//! Canonical Encoding Rules (CER) Writer
+//!
+//! This encoding enforces one canonical representation of the encoding,
+//! where the efficiency is biased towards the writer: constructed values
+//! use the indefinite length, and long strings are split in segments of
+//! 1000 bytes, so that nothing need to be known about the size of a value
+//! before starting to write it.
+pub mod writer;
+
+pub use self::writer::{OctetStringStream, Writer};
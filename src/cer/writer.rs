@@ -0,0 +1,420 @@
+use crate::header::*;
+use crate::objects::*;
+
+pub use crate::der::writer::Error;
+
+/// Maximum number of content bytes of a string segment
+const SEGMENT_SIZE: usize = 1000;
+
+/// Encoding of the header of a full string segment, minus the identifier
+const SEGMENT_LENGTH: Length = Length::Long {
+    nb_bytes: 2,
+    value: SEGMENT_SIZE as u32,
+};
+
+/// Size of the header of a full string segment
+const SEGMENT_HEADER_SIZE: usize = 4;
+
+/// ASN.1 CER Writer to a mutable buffer
+///
+/// Data is written front to back and never moved around, except for
+/// the last segment of a streamed octetstring
+pub struct Writer<'a> {
+    index: usize,
+    buf: &'a mut [u8],
+}
+
+impl<'a> Writer<'a> {
+    /// create a new CER writer, with the buffer as the user allocated write buffer
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Writer { index: 0, buf }
+    }
+
+    fn check_length(&self, sz: usize) -> Result<(), Error> {
+        if self.index + sz > self.buf.len() {
+            return Err(Error::BufferTooSmall(self.buf.len()));
+        }
+        Ok(())
+    }
+
+    fn identifier(&mut self, pc: PC, tag: u32) -> Result<(), Error> {
+        let identifier = Identifier {
+            pc,
+            class: Class::Universal,
+            tag: TagEncoded::new_smallest(tag),
+        };
+        let sz = identifier.size_bytes();
+        self.check_length(sz)?;
+        identifier.encode(&mut self.buf[self.index..]);
+        self.index += sz;
+        Ok(())
+    }
+
+    fn length(&mut self, length: Length) -> Result<(), Error> {
+        let sz = length.size_bytes();
+        self.check_length(sz)?;
+        length.encode(&mut self.buf[self.index..]);
+        self.index += sz;
+        Ok(())
+    }
+
+    fn bytes(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.check_length(data.len())?;
+        let end_index = self.index + data.len();
+        self.buf[self.index..end_index].copy_from_slice(data);
+        self.index += data.len();
+        Ok(())
+    }
+
+    fn copy_data(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.length(Length::new_smallest(data.len()))?;
+        self.bytes(data)
+    }
+
+    fn primitive(&mut self, tag: u32, data: &[u8]) -> Result<(), Error> {
+        self.identifier(PC::Primitive, tag)?;
+        self.copy_data(data)
+    }
+
+    fn end_of_contents(&mut self) -> Result<(), Error> {
+        self.bytes(&[0, 0])
+    }
+
+    fn constructed<F, R>(&mut self, tag: u32, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<R, Error>,
+    {
+        self.identifier(PC::Constructed, tag)?;
+        self.length(Length::Indefinite)?;
+        let r = f(self)?;
+        self.end_of_contents()?;
+        Ok(r)
+    }
+
+    // write a string, primitive if small enough, otherwise as a constructed
+    // of segments of 1000 bytes
+    fn string(&mut self, tag: u32, data: &[u8]) -> Result<(), Error> {
+        if data.len() <= SEGMENT_SIZE {
+            return self.primitive(tag, data);
+        }
+        self.constructed(tag, |w| {
+            for segment in data.chunks(SEGMENT_SIZE) {
+                w.primitive(tag, segment)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Write a boolean to the CER writer
+    pub fn bool(&mut self, b: bool) -> Result<(), Error> {
+        let v = if b { [0xff] } else { [0] };
+        self.primitive(constants::TAG_BOOLEAN, &v)
+    }
+
+    /// Write an Integer to the CER writer
+    pub fn integer(&mut self, integer: &Integer) -> Result<(), Error> {
+        self.primitive(constants::TAG_INTEGER, integer.as_ref())
+    }
+
+    /// Write an Enumerated to the CER writer
+    pub fn enumerated(&mut self, enumerated: &Enumerated) -> Result<(), Error> {
+        self.primitive(constants::TAG_ENUMERATED, enumerated.as_ref())
+    }
+
+    /// Write a bitstring to the CER writer
+    ///
+    /// If the bitstring is bigger than 1000 bytes, it is written as a
+    /// constructed of segments, each starting with their own unused bits byte
+    pub fn bitstring(&mut self, obj: &BitString) -> Result<(), Error> {
+        let tag = constants::TAG_BIT_STRING;
+        if obj.as_ref().len() <= SEGMENT_SIZE {
+            return self.primitive(tag, obj.as_ref());
+        }
+        let data = obj.data_bytes();
+        let bits_unused = obj.bits_unused() as u8;
+        self.constructed(tag, |w| {
+            let mut segments = data.chunks(SEGMENT_SIZE - 1).peekable();
+            while let Some(segment) = segments.next() {
+                let unused = if segments.peek().is_none() {
+                    bits_unused
+                } else {
+                    0
+                };
+                w.identifier(PC::Primitive, tag)?;
+                w.length(Length::new_smallest(1 + segment.len()))?;
+                w.bytes(&[unused])?;
+                w.bytes(segment)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Write a octetstring to the CER writer
+    ///
+    /// If the octetstring is bigger than 1000 bytes, it is written as a constructed of segments
+    pub fn octetstring(&mut self, obj: &[u8]) -> Result<(), Error> {
+        self.string(constants::TAG_OCTET_STRING, obj)
+    }
+
+    /// Start writing an octetstring of unknown size to the CER writer
+    ///
+    /// The data is given incrementally to the returned stream,
+    /// and the octetstring is terminated by calling `finish` on it.
+    /// If the stream is dropped without being finished, everything written
+    /// since the start of the octetstring is discarded.
+    pub fn octetstring_stream(&mut self) -> Result<OctetStringStream<'_, 'a>, Error> {
+        let start = self.index;
+        // reserve space for the constructed header and the first segment header,
+        // which is enough for the primitive header if the data ends up being small
+        self.check_length(2 + SEGMENT_HEADER_SIZE)?;
+        self.index += 2 + SEGMENT_HEADER_SIZE;
+        Ok(OctetStringStream {
+            segment_data: self.index,
+            writer: self,
+            start,
+            nb_segments: 0,
+            finished: false,
+        })
+    }
+
+    /// Write a null to the CER writer
+    pub fn null(&mut self) -> Result<(), Error> {
+        self.primitive(constants::TAG_NULL, &[])
+    }
+
+    /// Write a utf8 string to the CER writer
+    ///
+    /// If the string is bigger than 1000 bytes, it is written as a constructed of segments
+    pub fn utf8_string(&mut self, str: &str) -> Result<(), Error> {
+        self.string(constants::TAG_UTF8_STRING, str.as_bytes())
+    }
+
+    /// Write a sequence to the CER writer, using the indefinite length
+    pub fn sequence<F, R>(&mut self, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<R, Error>,
+    {
+        self.constructed(constants::TAG_SEQUENCE, f)
+    }
+
+    /// Get the current position in the Writer, which is also the number of byte written
+    pub fn current_position(&self) -> usize {
+        self.index
+    }
+
+    /// Return the inner sub-slice with a valid CER stream of data
+    pub fn finish<'b: 'a>(&'b self) -> &'a [u8] {
+        &self.buf[0..self.index]
+    }
+}
+
+/// Octetstring being written incrementally to a CER Writer
+///
+/// Each segment header is reserved before writing its data, and filled
+/// when the segment is full, so that only the last segment need to be
+/// moved when its size is known.
+///
+/// The stream need to be terminated with `finish`, otherwise the writer
+/// is rolled back to its position before the octetstring on drop.
+#[must_use = "the octetstring is discarded if the stream is not finished"]
+pub struct OctetStringStream<'w, 'a> {
+    writer: &'w mut Writer<'a>,
+    // position of the start of the octetstring
+    start: usize,
+    // position of the data of the segment being written
+    segment_data: usize,
+    // number of full segments before the one being written
+    nb_segments: usize,
+    // whether the octetstring has been terminated successfully
+    finished: bool,
+}
+
+impl<'w, 'a> OctetStringStream<'w, 'a> {
+    fn segment_header(&mut self, pos: usize, length: Length) {
+        let identifier = Identifier {
+            pc: PC::Primitive,
+            class: Class::Universal,
+            tag: TagEncoded::Short(constants::TAG_OCTET_STRING as u8),
+        };
+        let sz = identifier.encode(&mut self.writer.buf[pos..]);
+        length.encode(&mut self.writer.buf[pos + sz..]);
+    }
+
+    /// Append data to the octetstring
+    pub fn write(&mut self, mut data: &[u8]) -> Result<(), Error> {
+        while !data.is_empty() {
+            let in_segment = self.writer.index - self.segment_data;
+            if in_segment == SEGMENT_SIZE {
+                // the current segment is full and more data is coming
+                self.writer.check_length(SEGMENT_HEADER_SIZE)?;
+                self.segment_header(self.segment_data - SEGMENT_HEADER_SIZE, SEGMENT_LENGTH);
+                self.nb_segments += 1;
+                self.writer.index += SEGMENT_HEADER_SIZE;
+                self.segment_data = self.writer.index;
+                continue;
+            }
+            let n = core::cmp::min(SEGMENT_SIZE - in_segment, data.len());
+            self.writer.bytes(&data[0..n])?;
+            data = &data[n..];
+        }
+        Ok(())
+    }
+
+    /// Terminate the octetstring
+    ///
+    /// On error, the writer is rolled back to its position before the octetstring
+    pub fn finish(mut self) -> Result<(), Error> {
+        self.terminate()?;
+        self.finished = true;
+        Ok(())
+    }
+
+    fn terminate(&mut self) -> Result<(), Error> {
+        let data_len = self.writer.index - self.segment_data;
+        let length = Length::new_smallest(data_len);
+        if self.nb_segments == 0 {
+            // everything fit in one segment, write it as a primitive
+            let header_size = 1 + length.size_bytes();
+            let dest = self.start + header_size;
+            self.writer
+                .buf
+                .copy_within(self.segment_data..self.writer.index, dest);
+            self.writer.index = dest + data_len;
+            self.segment_header(self.start, length);
+            return Ok(());
+        }
+        let identifier = Identifier {
+            pc: PC::Constructed,
+            class: Class::Universal,
+            tag: TagEncoded::Short(constants::TAG_OCTET_STRING as u8),
+        };
+        let sz = identifier.encode(&mut self.writer.buf[self.start..]);
+        Length::Indefinite.encode(&mut self.writer.buf[self.start + sz..]);
+
+        // last segment is never empty, and need to be moved if its header is smaller
+        let header_pos = self.segment_data - SEGMENT_HEADER_SIZE;
+        let header_size = 1 + length.size_bytes();
+        let dest = header_pos + header_size;
+        self.writer
+            .buf
+            .copy_within(self.segment_data..self.writer.index, dest);
+        self.writer.index = dest + data_len;
+        self.segment_header(header_pos, length);
+        self.writer.end_of_contents()
+    }
+}
+
+impl<'w, 'a> Drop for OctetStringStream<'w, 'a> {
+    fn drop(&mut self) {
+        // the reserved headers are not filled, so nothing written is valid
+        if !self.finished {
+            self.writer.index = self.start;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ber;
+    use alloc::vec::Vec;
+
+    #[test]
+    pub fn encode_sequence() {
+        let mut buf = [0u8; 64];
+        let mut writer = Writer::new(&mut buf);
+        writer
+            .sequence(|writer| {
+                writer.sequence(|writer| writer.octetstring(&[1, 2, 3]))?;
+                writer.bool(true)
+            })
+            .expect("outer sequence");
+        assert_eq!(
+            writer.finish(),
+            &b"\x30\x80\x30\x80\x04\x03\x01\x02\x03\x00\x00\x01\x01\xff\x00\x00"[..]
+        );
+    }
+
+    #[test]
+    pub fn encode_long_octetstring() {
+        let data: Vec<u8> = (0..2500u32).map(|i| i as u8).collect();
+        let mut buf = [0u8; 4096];
+        let mut writer = Writer::new(&mut buf);
+        writer.octetstring(&data).expect("octetstring");
+        let slice = writer.finish();
+        assert_eq!(&slice[0..6], &b"\x24\x80\x04\x82\x03\xe8"[..]);
+        assert_eq!(slice.len(), 2 + 4 + 1000 + 4 + 1000 + 4 + 500 + 2);
+
+        let mut reader = ber::Reader::new(slice);
+        let segments = reader.octetstring().expect("ber octetstring");
+        reader.done().expect("done");
+        let lens: Vec<usize> = segments.clone().map(|s| s.len()).collect();
+        assert_eq!(lens, vec![1000, 1000, 500]);
+        let mut out = [0u8; 2500];
+        assert_eq!(segments.copy_to_slice(&mut out), Some(&data[..]));
+    }
+
+    #[test]
+    pub fn encode_long_bitstring() {
+        let mut raw: Vec<u8> = vec![3];
+        raw.extend((0..1200u32).map(|i| (i as u8) & 0xf8));
+        let bitstring = BitString::from_raw_slice(&raw);
+        let mut buf = [0u8; 2048];
+        let mut writer = Writer::new(&mut buf);
+        writer.bitstring(bitstring).expect("bitstring");
+        let slice = writer.finish();
+        assert_eq!(&slice[0..7], &b"\x23\x80\x03\x82\x03\xe8\x00"[..]);
+
+        let mut reader = ber::Reader::new(slice);
+        let segments = reader.bitstring().expect("ber bitstring");
+        reader.done().expect("done");
+        assert_eq!(segments.bits(), bitstring.bits());
+    }
+
+    #[test]
+    pub fn encode_octetstring_stream() {
+        let data: Vec<u8> = (0..2100u32).map(|i| i as u8).collect();
+        for size in &[0usize, 5, 200, 999, 1000, 1001, 2000, 2100] {
+            let mut buf = [0u8; 4096];
+            let mut writer = Writer::new(&mut buf);
+            let mut stream = writer.octetstring_stream().expect("stream");
+            for chunk in data[0..*size].chunks(7) {
+                stream.write(chunk).expect("write");
+            }
+            stream.finish().expect("finish");
+            writer.null().expect("null");
+
+            let mut expected_buf = [0u8; 4096];
+            let mut expected = Writer::new(&mut expected_buf);
+            expected.octetstring(&data[0..*size]).expect("octetstring");
+            expected.null().expect("null");
+            assert_eq!(writer.finish(), expected.finish(), "size {}", size);
+        }
+    }
+
+    #[test]
+    pub fn encode_octetstring_stream_unfinished() {
+        let mut buf = [0u8; 64];
+        let mut writer = Writer::new(&mut buf);
+        writer.null().expect("null");
+        let r = (|| {
+            let mut stream = writer.octetstring_stream()?;
+            stream.write(&[1, 2, 3])?;
+            stream.write(&[0; 100])?;
+            stream.finish()
+        })();
+        assert!(matches!(r, Err(Error::BufferTooSmall(64))));
+        assert_eq!(writer.current_position(), 2);
+
+        // not enough space for the end-of-contents
+        let mut buf = [0u8; 2214];
+        let mut writer = Writer::new(&mut buf);
+        let mut stream = writer.octetstring_stream().expect("stream");
+        stream.write(&[0; 2200]).expect("write");
+        assert!(stream.finish().is_err());
+        assert_eq!(writer.current_position(), 0);
+
+        writer.bool(true).expect("bool");
+        assert_eq!(writer.finish(), &b"\x01\x01\xff"[..]);
+    }
+}
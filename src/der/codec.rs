@@ -0,0 +1,492 @@
+//! Traits to decode and encode types with the DER Reader and Writer
+//!
+//! The built-in types map to their ASN.1 counterpart, and are composable:
+//!
+//! * tuples are encoded as a SEQUENCE of each of their elements
+//! * `Option<T>` is an OPTIONAL element, absent when the next identifier is not one of a `T`
+//! * slices are encoded as a SEQUENCE OF, and decoded lazily with `SequenceOf`
+use super::reader::{Error as ReadError, Reader};
+use super::tlv::Tlv;
+use super::writer::{Error as WriteError, SizeCounter, Writer};
+use crate::header::constants::*;
+use crate::header::{Class, Identifier, PC};
+use crate::objects::*;
+use core::marker::PhantomData;
+
+/// Type that can be decoded from a DER Reader
+pub trait DerDecode<'a>: Sized {
+    /// Check if an element with this identifier is decoded as this type
+    ///
+    /// This is how the presence of an OPTIONAL element, or the alternative
+    /// of a CHOICE, is found without reading the element
+    fn can_decode(identifier: &Identifier) -> bool;
+
+    /// Decode the next element of the reader
+    fn decode(reader: &mut Reader<'a>) -> Result<Self, ReadError>;
+}
+
+/// Type that can be encoded to a DER Writer
+pub trait DerEncode {
+    /// Encode the value as the next element of the writer
+    fn encode(&self, writer: &mut Writer<'_>) -> Result<(), WriteError>;
+
+    /// Return the size of the DER encoding of the value
+    fn encoded_len(&self) -> Result<usize, WriteError> {
+        let mut counter = SizeCounter::new();
+        self.encode(&mut counter)?;
+        Ok(counter.finish())
+    }
+}
+
+impl<T: DerEncode + ?Sized> DerEncode for &T {
+    fn encode(&self, writer: &mut Writer<'_>) -> Result<(), WriteError> {
+        (**self).encode(writer)
+    }
+}
+
+// check the identifier is the universal one of the given tag and encoding
+fn is_universal(identifier: &Identifier, pc: PC, tag: u32) -> bool {
+    identifier.class == Class::Universal && identifier.pc == pc && identifier.tag.value() == tag
+}
+
+macro_rules! codec_reader_writer {
+    ($ty: ty, $owned: ty, $read: ident, $write: ident, $tag: ident) => {
+        impl<'a> DerDecode<'a> for $ty {
+            fn can_decode(identifier: &Identifier) -> bool {
+                is_universal(identifier, PC::Primitive, $tag)
+            }
+
+            fn decode(reader: &mut Reader<'a>) -> Result<Self, ReadError> {
+                reader.$read()
+            }
+        }
+
+        impl DerEncode for $owned {
+            fn encode(&self, writer: &mut Writer<'_>) -> Result<(), WriteError> {
+                writer.$write(self)
+            }
+        }
+    };
+}
+
+codec_reader_writer!(&'a Integer, Integer, integer, integer, TAG_INTEGER);
+codec_reader_writer!(
+    &'a Enumerated,
+    Enumerated,
+    enumerated,
+    enumerated,
+    TAG_ENUMERATED
+);
+codec_reader_writer!(
+    &'a BitString,
+    BitString,
+    bitstring,
+    bitstring,
+    TAG_BIT_STRING
+);
+codec_reader_writer!(&'a [u8], [u8], octetstring, octetstring, TAG_OCTET_STRING);
+codec_reader_writer!(&'a str, str, utf8_string, utf8_string, TAG_UTF8_STRING);
+
+codec_reader_writer!(&'a OID, OID, oid, oid, TAG_OID);
+codec_reader_writer!(
+    &'a NumericString,
+    NumericString,
+    numeric_string,
+    numeric_string,
+    TAG_NUMERIC_STRING
+);
+codec_reader_writer!(
+    &'a PrintableString,
+    PrintableString,
+    printable_string,
+    printable_string,
+    TAG_PRINTABLE_STRING
+);
+codec_reader_writer!(
+    &'a T61String,
+    T61String,
+    t61_string,
+    t61_string,
+    TAG_T61_STRING
+);
+codec_reader_writer!(
+    &'a IA5String,
+    IA5String,
+    ia5_string,
+    ia5_string,
+    TAG_IA5_STRING
+);
+codec_reader_writer!(
+    &'a VisibleString,
+    VisibleString,
+    visible_string,
+    visible_string,
+    TAG_VISIBLE_STRING
+);
+codec_reader_writer!(
+    &'a UniversalString,
+    UniversalString,
+    universal_string,
+    universal_string,
+    TAG_UNIVERSAL_STRING
+);
+codec_reader_writer!(
+    &'a BMPString,
+    BMPString,
+    bmp_string,
+    bmp_string,
+    TAG_BMP_STRING
+);
+
+impl<'a> DerDecode<'a> for Tlv<'a> {
+    /// Any element is decoded as a Tlv
+    fn can_decode(_identifier: &Identifier) -> bool {
+        true
+    }
+
+    fn decode(reader: &mut Reader<'a>) -> Result<Self, ReadError> {
+        reader.any_raw()
+    }
+}
+
+impl<'a> DerEncode for Tlv<'a> {
+    fn encode(&self, writer: &mut Writer<'_>) -> Result<(), WriteError> {
+        writer.raw_tlv(self)
+    }
+}
+
+impl<'a> DerDecode<'a> for AnyString<'a> {
+    fn can_decode(identifier: &Identifier) -> bool {
+        [
+            TAG_UTF8_STRING,
+            TAG_NUMERIC_STRING,
+            TAG_PRINTABLE_STRING,
+            TAG_T61_STRING,
+            TAG_IA5_STRING,
+            TAG_VISIBLE_STRING,
+            TAG_UNIVERSAL_STRING,
+            TAG_BMP_STRING,
+        ]
+        .iter()
+        .any(|tag| is_universal(identifier, PC::Primitive, *tag))
+    }
+
+    fn decode(reader: &mut Reader<'a>) -> Result<Self, ReadError> {
+        reader.any_string()
+    }
+}
+
+impl<'a> DerEncode for AnyString<'a> {
+    fn encode(&self, writer: &mut Writer<'_>) -> Result<(), WriteError> {
+        writer.any_string(self)
+    }
+}
+
+codec_reader_writer!(&'a Real, Real, real, real, TAG_REAL);
+
+impl<'a> DerDecode<'a> for f64 {
+    fn can_decode(identifier: &Identifier) -> bool {
+        is_universal(identifier, PC::Primitive, TAG_REAL)
+    }
+
+    fn decode(reader: &mut Reader<'a>) -> Result<Self, ReadError> {
+        reader.real()?.to_f64().ok_or(ReadError::RealInvalid)
+    }
+}
+
+impl DerEncode for f64 {
+    fn encode(&self, writer: &mut Writer<'_>) -> Result<(), WriteError> {
+        writer.real_f64(*self)
+    }
+}
+
+codec_reader_writer!(&'a UtcTime, UtcTime, utc_time, utc_time, TAG_UTC_TIME);
+codec_reader_writer!(
+    &'a GeneralizedTime,
+    GeneralizedTime,
+    generalized_time,
+    generalized_time,
+    TAG_GENERALIZED_TIME
+);
+codec_reader_writer!(
+    &'a RelativeOID,
+    RelativeOID,
+    relative_oid,
+    relative_oid,
+    TAG_RELATIVE_OID
+);
+
+macro_rules! codec_primitive_integer {
+    ($ty: ident, $to: ident, $write: ident, $wide: ty) => {
+        impl<'a> DerDecode<'a> for $ty {
+            fn can_decode(identifier: &Identifier) -> bool {
+                is_universal(identifier, PC::Primitive, TAG_INTEGER)
+            }
+
+            fn decode(reader: &mut Reader<'a>) -> Result<Self, ReadError> {
+                reader.integer()?.$to().ok_or(ReadError::IntegerOverflow)
+            }
+        }
+
+        impl DerEncode for $ty {
+            fn encode(&self, writer: &mut Writer<'_>) -> Result<(), WriteError> {
+                writer.$write(*self as $wide)
+            }
+        }
+    };
+}
+
+// u8 is not an INTEGER, as `[u8]` is already encoded as an OCTET STRING
+codec_primitive_integer!(u16, to_u16, integer_u64, u64);
+codec_primitive_integer!(u32, to_u32, integer_u64, u64);
+codec_primitive_integer!(u64, to_u64, integer_u64, u64);
+codec_primitive_integer!(i8, to_i8, integer_i64, i64);
+codec_primitive_integer!(i16, to_i16, integer_i64, i64);
+codec_primitive_integer!(i32, to_i32, integer_i64, i64);
+codec_primitive_integer!(i64, to_i64, integer_i64, i64);
+
+impl<'a> DerDecode<'a> for bool {
+    fn can_decode(identifier: &Identifier) -> bool {
+        is_universal(identifier, PC::Primitive, TAG_BOOLEAN)
+    }
+
+    fn decode(reader: &mut Reader<'a>) -> Result<Self, ReadError> {
+        reader.bool()
+    }
+}
+
+impl DerEncode for bool {
+    fn encode(&self, writer: &mut Writer<'_>) -> Result<(), WriteError> {
+        writer.bool(*self)
+    }
+}
+
+impl<'a> DerDecode<'a> for () {
+    fn can_decode(identifier: &Identifier) -> bool {
+        is_universal(identifier, PC::Primitive, TAG_NULL)
+    }
+
+    fn decode(reader: &mut Reader<'a>) -> Result<Self, ReadError> {
+        reader.null()
+    }
+}
+
+impl DerEncode for () {
+    fn encode(&self, writer: &mut Writer<'_>) -> Result<(), WriteError> {
+        writer.null()
+    }
+}
+
+impl<'a, T: DerDecode<'a>> DerDecode<'a> for Option<T> {
+    fn can_decode(identifier: &Identifier) -> bool {
+        T::can_decode(identifier)
+    }
+
+    /// Decode the element if present
+    ///
+    /// The element is considered absent when the reader is done,
+    /// or when the next identifier is not one of a `T`,
+    /// in which case nothing is consumed from the reader.
+    fn decode(reader: &mut Reader<'a>) -> Result<Self, ReadError> {
+        reader.optional()
+    }
+}
+
+impl<T: DerEncode> DerEncode for Option<T> {
+    /// Encode the element if present, otherwise nothing is written
+    fn encode(&self, writer: &mut Writer<'_>) -> Result<(), WriteError> {
+        match self {
+            None => Ok(()),
+            Some(v) => v.encode(writer),
+        }
+    }
+}
+
+impl<T: DerEncode> DerEncode for [T] {
+    /// Encode as a SEQUENCE OF
+    fn encode(&self, writer: &mut Writer<'_>) -> Result<(), WriteError> {
+        writer.sequence(|writer| {
+            for v in self.iter() {
+                v.encode(writer)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Lazy decoder of the elements of a SEQUENCE OF
+///
+/// Each element is decoded when iterating, and the iteration stops
+/// after the first error
+#[derive(Clone)]
+pub struct SequenceOf<'a, T> {
+    reader: Reader<'a>,
+    failed: bool,
+    phantom: PhantomData<T>,
+}
+
+impl<'a, T: DerDecode<'a>> DerDecode<'a> for SequenceOf<'a, T> {
+    fn can_decode(identifier: &Identifier) -> bool {
+        is_universal(identifier, PC::Constructed, TAG_SEQUENCE)
+    }
+
+    fn decode(reader: &mut Reader<'a>) -> Result<Self, ReadError> {
+        let reader = reader.sequence()?;
+        Ok(SequenceOf {
+            reader,
+            failed: false,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl<'a, T: DerDecode<'a>> Iterator for SequenceOf<'a, T> {
+    type Item = Result<T, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.reader.remaining().is_empty() {
+            return None;
+        }
+        let r = T::decode(&mut self.reader);
+        self.failed = r.is_err();
+        Some(r)
+    }
+}
+
+macro_rules! codec_tuple {
+    ($($name: ident $idx: tt),*) => {
+        impl<'a, $($name: DerDecode<'a>),*> DerDecode<'a> for ($($name,)*) {
+            fn can_decode(identifier: &Identifier) -> bool {
+                is_universal(identifier, PC::Constructed, TAG_SEQUENCE)
+            }
+
+            /// Decode a SEQUENCE of each element of the tuple
+            fn decode(reader: &mut Reader<'a>) -> Result<Self, ReadError> {
+                let mut seq = reader.sequence()?;
+                let v = ($($name::decode(&mut seq)?,)*);
+                seq.done()?;
+                Ok(v)
+            }
+        }
+
+        impl<$($name: DerEncode),*> DerEncode for ($($name,)*) {
+            /// Encode a SEQUENCE of each element of the tuple
+            fn encode(&self, writer: &mut Writer<'_>) -> Result<(), WriteError> {
+                writer.sequence(|writer| {
+                    $(self.$idx.encode(writer)?;)*
+                    Ok(())
+                })
+            }
+        }
+    };
+}
+
+codec_tuple!(A 0);
+codec_tuple!(A 0, B 1);
+codec_tuple!(A 0, B 1, C 2);
+codec_tuple!(A 0, B 1, C 2, D 3);
+codec_tuple!(A 0, B 1, C 2, D 3, E 4);
+codec_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+codec_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+codec_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    pub fn decode_tuples() {
+        let key_bs = b"\x30\x59\x30\x13\x06\x07\x2A\x86\x48\xCE\x3D\x02\x01\x06\x08\x2A\x86\x48\xCE\x3D\x03\x01\x07\x03\x42\x00\x04\xA4\x39\xEC\xD3\xCE\xAD\xFD\xDB\x8E\x50\x34\xFD\x99\x72\x45\x8C\xDC\xEB\xA9\xD3\x4E\x09\xF3\x47\x31\x4A\x48\x6C\x3C\x4E\x3C\x00\x43\x3A\x1C\x0A\x6D\xBE\xE2\xEF\x6D\x00\x8A\x10\xC9\xE3\xBE\x0F\x07\xD3\x31\x8E\x77\x44\x20\x14\xE6\x63\xC2\xAF\x19\x14\x8B\xAC";
+        let mut reader = Reader::new(key_bs);
+        let ((alg, curve), bits): ((&OID, Option<&OID>), &BitString) =
+            reader.decode().expect("public key");
+        reader.done().expect("done");
+        assert_eq!(alg.as_ref(), &b"\x2A\x86\x48\xCE\x3D\x02\x01"[..]);
+        assert!(curve.is_some());
+        assert_eq!(bits.bits(), 520);
+
+        // re-encode, and check that the encoding is the same
+        let mut buf = [0u8; 128];
+        let mut writer = Writer::new(&mut buf);
+        writer.encode(&((alg, curve), bits)).expect("encode");
+        assert_eq!(writer.finish(), &key_bs[..]);
+    }
+
+    #[test]
+    pub fn codec_optional() {
+        let mut buf = [0u8; 64];
+        let mut writer = Writer::new(&mut buf);
+        let v: (Option<bool>, (), Option<bool>) = (None, (), Some(false));
+        writer.encode(&v).expect("encode");
+        let slice = writer.finish();
+        assert_eq!(slice, &b"\x30\x05\x05\x00\x01\x01\x00"[..]);
+
+        let mut reader = Reader::new(slice);
+        let v2: (Option<bool>, (), Option<bool>) = reader.decode().expect("decode");
+        assert_eq!(v, v2);
+
+        // optional elements don't hide errors other than the type mismatch
+        let mut reader = Reader::new(&b"\x01\x02\x00\x00"[..]);
+        assert!(reader.decode::<Option<bool>>().is_err());
+
+        // the presence is decided by the outer identifier only, so a present
+        // but malformed element is an error
+        let mut reader = Reader::new(&b"\x30\x03\x02\x01\x00"[..]);
+        match reader.decode::<Option<(bool,)>>() {
+            Err(ReadError::ExpectedTag {
+                expected: 1,
+                got: 2,
+            }) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+        let mut reader = Reader::new(&b"\x01\x01\xff"[..]);
+        assert_eq!(reader.decode::<Option<(bool,)>>().expect("absent"), None);
+        assert_eq!(reader.current_position(), 0);
+    }
+
+    #[test]
+    pub fn codec_sequence_of() {
+        let values = [true, false, true];
+        let mut buf = [0u8; 64];
+        let mut writer = Writer::new(&mut buf);
+        writer.encode(&values[..]).expect("encode");
+        let slice = writer.finish();
+        assert_eq!(slice, &b"\x30\x09\x01\x01\xff\x01\x01\x00\x01\x01\xff"[..]);
+
+        let mut reader = Reader::new(slice);
+        let seq: SequenceOf<bool> = reader.decode().expect("decode");
+        let decoded: Vec<bool> = seq.collect::<Result<_, _>>().expect("elements");
+        assert_eq!(&decoded[..], &values[..]);
+
+        let mut reader = Reader::new(&b"\x30\x05\x01\x01\xff\x05\x00"[..]);
+        let seq: SequenceOf<bool> = reader.decode().expect("decode");
+        let decoded: Vec<Result<bool, ReadError>> = seq.collect();
+        assert_eq!(decoded.len(), 2);
+        assert!(decoded[1].is_err());
+    }
+
+    #[test]
+    pub fn codec_primitive_integers() {
+        let mut buf = [0u8; 64];
+        let mut writer = Writer::new(&mut buf);
+        let v: (u16, i16, i64, u64, f64) = (200, -300, i64::MIN, 0, -0.75);
+        writer.encode(&v).expect("encode");
+        let slice = writer.finish();
+
+        let mut reader = Reader::new(slice);
+        let v2: (u16, i16, i64, u64, f64) = reader.decode().expect("decode");
+        assert_eq!(v, v2);
+
+        let mut reader = Reader::new(&b"\x02\x02\x00\x80"[..]);
+        assert!(matches!(
+            reader.decode::<i8>(),
+            Err(ReadError::IntegerOverflow)
+        ));
+        let mut reader = Reader::new(&b"\x02\x01\xff"[..]);
+        assert!(matches!(
+            reader.decode::<u32>(),
+            Err(ReadError::IntegerOverflow)
+        ));
+    }
+}
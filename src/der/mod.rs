@@ -6,10 +6,26 @@
 //! This is the usual format of cryptographic material, although in few
 //! cases, some cryptographic material need to use BER relaxed rules for
 //! reading as their encoding wasn't done strictly.
+pub mod codec;
 pub mod reader;
+pub mod tlv;
+#[macro_use]
 pub mod writer;
+pub mod reverse;
 
-pub use self::{reader::Reader, writer::Writer};
+pub use self::{
+    codec::{DerDecode, DerEncode, SequenceOf},
+    reader::{LimitKind, Reader, ReaderConfig},
+    reverse::ReverseWriter,
+    tlv::Tlv,
+    writer::{SizeCounter, Writer},
+};
+
+#[cfg(feature = "alloc")]
+pub use self::writer::{encode_to_vec, VecWriter};
+
+#[cfg(feature = "derive")]
+pub use basn1_derive::{DerDecode, DerEncode};
 
 #[cfg(test)]
 mod tests {
@@ -35,7 +51,7 @@ mod tests {
         let mut seqreader = reader.sequence().expect("outer sequence");
         let mut inreader = seqreader.sequence().expect("inner sequence");
         let b = seqreader.bool().expect("bool");
-        assert_eq!(b, true);
+        assert!(b);
         seqreader.done().expect("outer done");
         let ostring2 = inreader.octetstring().expect("octetstring");
         inreader.done().expect("inner done");
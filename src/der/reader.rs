@@ -1,38 +1,276 @@
+use super::codec::DerDecode;
+use super::tlv::Tlv;
 use crate::header::constants;
-use crate::header::{Class, Identifier, Length, PC};
+use crate::header::{Class, Identifier, IdentifierDecodeError, Length, LengthDecodeError, PC};
 use crate::intenc::Integer8Bit;
 use crate::objects::*;
+#[cfg(feature = "alloc")]
+use alloc::rc::Rc;
+use core::cell::Cell;
+use core::fmt;
+
+macro_rules! reader_implicit {
+    ($name: ident, $read: ident, $ty: ty, $asn1: expr) => {
+        #[doc = concat!("Get the next ", $asn1, " with a context specific IMPLICIT tag from the stream")]
+        pub fn $name(&mut self, tag: u32) -> Result<$ty, Error> {
+            self.with_implicit(tag, Self::$read)
+        }
+    };
+}
+
+macro_rules! reader_optional {
+    ($name: ident, $read: ident, $ty: ty, $tag: ident, $asn1: expr) => {
+        #[doc = concat!("Get the next ", $asn1, " from the stream if present")]
+        pub fn $name(&mut self) -> Result<Option<$ty>, Error> {
+            self.if_tag(Class::Universal, constants::$tag, Self::$read)
+        }
+    };
+}
+
+macro_rules! reader_string {
+    ($name: ident, $optional: ident, $implicit: ident, $ty: ident, $tag: ident, $err: ident, $asn1: expr) => {
+        #[doc = concat!("Get the next ", $asn1, " from the stream")]
+        pub fn $name(&mut self) -> Result<&'a $ty, Error> {
+            let len = self.next_assume(PC::Primitive, constants::$tag)?;
+            let sub = self.subslice(len)?;
+            $ty::from_slice(sub).map_err(|_| Error::$err)
+        }
+
+        reader_optional!($optional, $name, &'a $ty, $tag, $asn1);
+        reader_implicit!($implicit, $name, &'a $ty, $asn1);
+    };
+}
+
+/// Limits of a Reader, when reading untrusted input
+///
+/// The limits apply to the reader created with `Reader::with_config`, and all the
+/// readers derived from it (e.g. the content of a sequence), which all share the
+/// count of elements read. Enforcing limits requires the `alloc` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReaderConfig {
+    /// Maximum nesting of constructed elements
+    pub max_depth: usize,
+    /// Maximum size of the content of an element
+    pub max_element_size: usize,
+    /// Maximum number of elements read
+    pub max_elements: usize,
+}
+
+impl Default for ReaderConfig {
+    /// A configuration without limits, the same as `Reader::new`
+    fn default() -> Self {
+        ReaderConfig::new(usize::MAX, usize::MAX, usize::MAX)
+    }
+}
+
+impl ReaderConfig {
+    /// Create a new configuration with the given limits
+    pub fn new(max_depth: usize, max_element_size: usize, max_elements: usize) -> Self {
+        ReaderConfig {
+            max_depth,
+            max_element_size,
+            max_elements,
+        }
+    }
+}
+
+/// The limit of a ReaderConfig that has been exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitKind {
+    Depth,
+    ElementSize,
+    Elements,
+}
+
+// limits of a reader, with the count of elements read shared by all the readers
+// derived from the same top level reader
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+struct Limits {
+    config: ReaderConfig,
+    elements: Rc<Cell<usize>>,
+}
 
 /// ASN.1 DER Reader on slice
 #[derive(Clone)]
 pub struct Reader<'a> {
     index: usize,
     slice: &'a [u8],
+    // limits when reading untrusted input, and the nesting depth of this reader
+    #[cfg(feature = "alloc")]
+    limits: Option<Limits>,
+    depth: usize,
+    // offset of the slice relative to the start of the top level stream,
+    // used to report absolute positions in errors
+    offset: usize,
+    // class and tag replacing the universal tag of the next element,
+    // when reading an IMPLICIT tagged element
+    implicit: Option<(Class, u32)>,
 }
 
 /// DER ASN.1 Error when reading data
 #[derive(Debug, Clone)]
 pub enum Error {
-    ExpectedCType { expected: PC, got: PC },
-    ExpectedTag { expected: u32, got: u32 },
-    ExpectedClass { expected: Class, got: Class },
+    ExpectedCType {
+        expected: PC,
+        got: PC,
+    },
+    ExpectedTag {
+        expected: u32,
+        got: u32,
+    },
+    ExpectedClass {
+        expected: Class,
+        got: Class,
+    },
     IndefiniteLengthDER,
+    /// The identifier starting at offset is missing or incomplete
+    IdentifierTruncated {
+        offset: usize,
+    },
+    /// The identifier starting at offset is not a valid encoding
+    IdentifierInvalid {
+        offset: usize,
+    },
+    /// The length starting at offset is missing or incomplete
+    LengthTruncated {
+        offset: usize,
+    },
+    /// The length starting at offset is not a valid encoding
+    LengthInvalid {
+        offset: usize,
+    },
+    /// The length starting at offset is not in the minimal DER encoding
+    LengthNotCanonical {
+        offset: usize,
+    },
+    /// The content starting at offset needs more bytes than remaining in the stream
+    DataTruncated {
+        offset: usize,
+        length: usize,
+        remaining: usize,
+    },
     BoolLengthInvalid(usize),
     BoolEncodingInvalid(u8),
     BitStringEncodingEmpty,
     BitStringEncodingInvalidStart,
     BitStringEncodingInvalidEnd,
     IntegerNotCanonical,
+    IntegerOverflow,
     Utf8Invalid,
+    NumericStringInvalid,
+    PrintableStringInvalid,
+    IA5StringInvalid,
+    VisibleStringInvalid,
+    UniversalStringInvalid,
+    BMPStringInvalid,
     NullEncodingInvalid,
+    RealInvalid,
+    RealNotCanonical,
     OIDInvalid,
-    ReaderNotTerminated { index: usize, len: usize },
+    RelativeOIDInvalid,
+    UtcTimeInvalid,
+    GeneralizedTimeInvalid,
+    /// The element at offset is encoded with its DEFAULT value, which DER forbids
+    DefaultValueEncoded {
+        offset: usize,
+    },
+    /// The element at offset is none of the alternatives of a CHOICE
+    ChoiceInvalid {
+        offset: usize,
+    },
+    /// A limit of the ReaderConfig has been exceeded by the element at offset
+    LimitExceeded {
+        kind: LimitKind,
+        offset: usize,
+    },
+    ReaderNotTerminated {
+        index: usize,
+        len: usize,
+    },
 }
 
-fn assume(header: &Identifier, pc: PC, tag: u32) -> Result<(), Error> {
-    if header.class != Class::Universal {
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ExpectedCType { expected, got } => {
+                write!(f, "expected {:?} element, got {:?}", expected, got)
+            }
+            Error::ExpectedTag { expected, got } => {
+                write!(f, "expected tag {}, got tag {}", expected, got)
+            }
+            Error::ExpectedClass { expected, got } => {
+                write!(f, "expected {:?} class, got {:?} class", expected, got)
+            }
+            Error::IndefiniteLengthDER => write!(f, "indefinite length is not allowed in DER"),
+            Error::IdentifierTruncated { offset } => {
+                write!(f, "identifier truncated at offset {}", offset)
+            }
+            Error::IdentifierInvalid { offset } => {
+                write!(f, "invalid identifier at offset {}", offset)
+            }
+            Error::LengthTruncated { offset } => write!(f, "length truncated at offset {}", offset),
+            Error::LengthInvalid { offset } => write!(f, "invalid length at offset {}", offset),
+            Error::LengthNotCanonical { offset } => {
+                write!(f, "length not in DER form at offset {}", offset)
+            }
+            Error::DataTruncated {
+                offset,
+                length,
+                remaining,
+            } => write!(
+                f,
+                "content at offset {} needs {} bytes but only {} remaining",
+                offset, length, remaining
+            ),
+            Error::BoolLengthInvalid(len) => write!(f, "invalid BOOLEAN length {}", len),
+            Error::BoolEncodingInvalid(v) => write!(f, "invalid BOOLEAN value {:#04x}", v),
+            Error::BitStringEncodingEmpty => write!(f, "empty BIT STRING encoding"),
+            Error::BitStringEncodingInvalidStart => write!(f, "invalid BIT STRING unused bits"),
+            Error::BitStringEncodingInvalidEnd => write!(f, "BIT STRING unused bits not zero"),
+            Error::IntegerNotCanonical => write!(f, "INTEGER not in minimal encoding"),
+            Error::IntegerOverflow => write!(f, "INTEGER overflow"),
+            Error::Utf8Invalid => write!(f, "invalid UTF8String"),
+            Error::NumericStringInvalid => write!(f, "invalid NumericString"),
+            Error::PrintableStringInvalid => write!(f, "invalid PrintableString"),
+            Error::IA5StringInvalid => write!(f, "invalid IA5String"),
+            Error::VisibleStringInvalid => write!(f, "invalid VisibleString"),
+            Error::UniversalStringInvalid => write!(f, "invalid UniversalString"),
+            Error::BMPStringInvalid => write!(f, "invalid BMPString"),
+            Error::NullEncodingInvalid => write!(f, "invalid NULL encoding"),
+            Error::RealInvalid => write!(f, "invalid REAL"),
+            Error::RealNotCanonical => write!(f, "REAL not in DER form"),
+            Error::OIDInvalid => write!(f, "invalid OBJECT IDENTIFIER"),
+            Error::RelativeOIDInvalid => write!(f, "invalid RELATIVE-OID"),
+            Error::UtcTimeInvalid => write!(f, "invalid UTCTime"),
+            Error::GeneralizedTimeInvalid => write!(f, "invalid GeneralizedTime"),
+            Error::DefaultValueEncoded { offset } => {
+                write!(f, "DEFAULT value encoded at offset {}", offset)
+            }
+            Error::ChoiceInvalid { offset } => {
+                write!(
+                    f,
+                    "no CHOICE alternative for the element at offset {}",
+                    offset
+                )
+            }
+            Error::LimitExceeded { kind, offset } => {
+                write!(f, "{:?} limit exceeded at offset {}", kind, offset)
+            }
+            Error::ReaderNotTerminated { index, len } => {
+                write!(f, "reader not terminated at index {} of {}", index, len)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+fn assume(header: &Identifier, class: Class, pc: PC, tag: u32) -> Result<(), Error> {
+    if header.class != class {
         return Err(Error::ExpectedClass {
-            expected: Class::Universal,
+            expected: class,
             got: header.class,
         });
     }
@@ -73,20 +311,115 @@ where
 impl<'a> Reader<'a> {
     /// Create a new DER Reader where the read buffer is given by the user
     pub fn new(slice: &'a [u8]) -> Self {
-        Reader { slice, index: 0 }
+        Reader {
+            slice,
+            index: 0,
+            #[cfg(feature = "alloc")]
+            limits: None,
+            depth: 0,
+            offset: 0,
+            implicit: None,
+        }
+    }
+
+    /// Create a new DER Reader enforcing the limits of the configuration
+    #[cfg(feature = "alloc")]
+    pub fn with_config(slice: &'a [u8], config: ReaderConfig) -> Self {
+        Reader {
+            limits: Some(Limits {
+                config,
+                elements: Rc::new(Cell::new(0)),
+            }),
+            ..Reader::new(slice)
+        }
+    }
+
+    /// Return the number of elements read by this reader and all the readers
+    /// sharing its configuration, which is only counted with a configuration
+    #[cfg(feature = "alloc")]
+    pub fn elements_read(&self) -> usize {
+        self.limits.as_ref().map_or(0, |l| l.elements.get())
+    }
+
+    /// absolute offset of the current position in the top level stream
+    fn position(&self) -> usize {
+        self.offset + self.index
+    }
+
+    fn decode_identifier(&self) -> Result<(Identifier, usize), Error> {
+        Identifier::decode(&self.slice[self.index..]).map_err(|e| {
+            let offset = self.position();
+            match e {
+                IdentifierDecodeError::EmptyHeader
+                | IdentifierDecodeError::TagEncodingIncomplete => {
+                    Error::IdentifierTruncated { offset }
+                }
+                IdentifierDecodeError::TagEncodingOverflow
+                | IdentifierDecodeError::TagEncodingNonCanonical => {
+                    Error::IdentifierInvalid { offset }
+                }
+            }
+        })
+    }
+
+    // check a limit of the configuration, if any, for the element at offset
+    #[cfg(feature = "alloc")]
+    fn check_limit<F>(&self, kind: LimitKind, offset: usize, exceeded: F) -> Result<(), Error>
+    where
+        F: FnOnce(&ReaderConfig, &Cell<usize>) -> bool,
+    {
+        match &self.limits {
+            Some(l) if exceeded(&l.config, &l.elements) => {
+                Err(Error::LimitExceeded { kind, offset })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn check_limit<F>(&self, _: LimitKind, _: usize, _: F) -> Result<(), Error>
+    where
+        F: FnOnce(&ReaderConfig, &Cell<usize>) -> bool,
+    {
+        Ok(())
     }
 
     fn next(&mut self) -> Result<(Identifier, Length), Error> {
-        let (hdr, sz) = Identifier::decode(&self.slice[self.index..]).unwrap();
+        let start = self.position();
+        self.check_limit(LimitKind::Elements, start, |config, elements| {
+            elements.set(elements.get() + 1);
+            elements.get() > config.max_elements
+        })?;
+        let (hdr, sz) = self.decode_identifier()?;
         self.index += sz;
-        let (len, sz) = Length::decode(&self.slice[self.index..]).unwrap();
+        let (len, sz) = Length::decode_der(&self.slice[self.index..]).map_err(|e| {
+            let offset = self.position();
+            match e {
+                LengthDecodeError::EncodingIncomplete => Error::LengthTruncated { offset },
+                LengthDecodeError::EncodingOverflow | LengthDecodeError::EncodingReserved => {
+                    Error::LengthInvalid { offset }
+                }
+                LengthDecodeError::NonMinimalLongForm
+                | LengthDecodeError::NonMinimalLeadingZero => Error::LengthNotCanonical { offset },
+            }
+        })?;
         self.index += sz;
+        self.check_limit(LimitKind::ElementSize, start, |config, _| {
+            len.value()
+                .is_some_and(|v| v as usize > config.max_element_size)
+        })?;
         Ok((hdr, len))
     }
 
     fn next_assume(&mut self, pc: PC, tag: u32) -> Result<Length, Error> {
+        self.next_assume_class(Class::Universal, pc, tag)
+    }
+
+    // an IMPLICIT tag being set replace the expected class and tag
+    fn next_assume_class(&mut self, class: Class, pc: PC, tag: u32) -> Result<Length, Error> {
+        let (class, tag) = self.implicit.take().unwrap_or((class, tag));
         let (hdr, len) = self.next()?;
-        assume(&hdr, pc, tag)?;
+        assume(&hdr, class, pc, tag)?;
         Ok(len)
     }
 
@@ -96,22 +429,134 @@ impl<'a> Reader<'a> {
             Length::Short(v) => v as usize,
             Length::Long { nb_bytes: _, value } => value as usize,
         };
+        let remaining = self.slice.len() - self.index;
+        if len > remaining {
+            return Err(Error::DataTruncated {
+                offset: self.position(),
+                length: len,
+                remaining,
+            });
+        }
         let sub = &self.slice[self.index..self.index + len];
         self.index += len;
         Ok(sub)
     }
 
     fn subslice_reader(&mut self, length: Length) -> Result<Reader<'a>, Error> {
+        let offset = self.position();
+        let depth = self.depth + 1;
+        self.check_limit(LimitKind::Depth, offset, |config, _| {
+            depth > config.max_depth
+        })?;
         let slice = self.subslice(length)?;
-        Ok(Self::new(slice))
+        Ok(Reader {
+            slice,
+            index: 0,
+            #[cfg(feature = "alloc")]
+            limits: self.limits.clone(),
+            depth,
+            offset,
+            implicit: None,
+        })
     }
 
-    /*
-    fn peek(&self) -> Result<Header, Error> {
-        let (hdr, _) = Header::decode(&self.slice[self.index..]).unwrap();
-        Ok(hdr)
+    /// Get the identifier of the next element, without consuming anything from the stream
+    ///
+    /// Nothing is returned if the stream is done
+    pub fn peek_identifier(&self) -> Result<Option<Identifier>, Error> {
+        if self.remaining().is_empty() {
+            return Ok(None);
+        }
+        self.decode_identifier().map(|(hdr, _)| Some(hdr))
+    }
+
+    /// Read the next element with f, only if its identifier has the given class and tag
+    ///
+    /// Nothing is consumed from the stream and nothing is returned if the stream
+    /// is done or if the next element doesn't match
+    pub fn if_tag<T, F>(&mut self, class: Class, tag: u32, f: F) -> Result<Option<T>, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<T, Error>,
+    {
+        match self.peek_identifier()? {
+            Some(hdr) if hdr.class == class && hdr.tag.value() == tag => f(self).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    reader_optional!(optional_bool, bool, bool, TAG_BOOLEAN, "boolean");
+    reader_optional!(
+        optional_integer,
+        integer,
+        &'a Integer,
+        TAG_INTEGER,
+        "integer"
+    );
+    reader_optional!(
+        optional_enumerated,
+        enumerated,
+        &'a Enumerated,
+        TAG_ENUMERATED,
+        "enumerated"
+    );
+    reader_optional!(
+        optional_bitstring,
+        bitstring,
+        &'a BitString,
+        TAG_BIT_STRING,
+        "bitstring"
+    );
+    reader_optional!(
+        optional_octetstring,
+        octetstring,
+        &'a [u8],
+        TAG_OCTET_STRING,
+        "octetstring"
+    );
+    reader_optional!(
+        optional_utf8_string,
+        utf8_string,
+        &'a str,
+        TAG_UTF8_STRING,
+        "utf8 string"
+    );
+    reader_optional!(optional_null, null, (), TAG_NULL, "null");
+    reader_optional!(optional_real, real, &'a Real, TAG_REAL, "real");
+    reader_optional!(optional_oid, oid, &'a OID, TAG_OID, "OID");
+    reader_optional!(
+        optional_relative_oid,
+        relative_oid,
+        &'a RelativeOID,
+        TAG_RELATIVE_OID,
+        "relative OID"
+    );
+    reader_optional!(
+        optional_utc_time,
+        utc_time,
+        &'a UtcTime,
+        TAG_UTC_TIME,
+        "UTC time"
+    );
+    reader_optional!(
+        optional_generalized_time,
+        generalized_time,
+        &'a GeneralizedTime,
+        TAG_GENERALIZED_TIME,
+        "generalized time"
+    );
+    reader_optional!(
+        optional_sequence,
+        sequence,
+        Reader<'a>,
+        TAG_SEQUENCE,
+        "sequence"
+    );
+
+    /// Get the next context specific EXPLICIT tagged element if present,
+    /// as a Reader over the inner element
+    pub fn optional_explicit(&mut self, tag: u32) -> Result<Option<Reader<'a>>, Error> {
+        self.if_tag(Class::Context, tag, |r| r.explicit(tag))
     }
-    */
 
     /// Get the next Tag / Length / Value triple, whichever it is
     pub fn anything(&mut self) -> Result<(Identifier, Length, &'a [u8]), Error> {
@@ -120,6 +565,16 @@ impl<'a> Reader<'a> {
         Ok((identifier, length, slice))
     }
 
+    /// Get the next element, whichever it is, retaining its full encoding
+    pub fn any_raw(&mut self) -> Result<Tlv<'a>, Error> {
+        let start = self.index;
+        let (identifier, length) = self.next()?;
+        let header_size = self.index - start;
+        self.subslice(length)?;
+        let raw = &self.slice[start..self.index];
+        Ok(Tlv::new(identifier, length, raw, header_size))
+    }
+
     /// Get the next boolean from the stream
     pub fn bool(&mut self) -> Result<bool, Error> {
         let len = self.next_assume(PC::Primitive, constants::TAG_BOOLEAN)?;
@@ -184,11 +639,102 @@ impl<'a> Reader<'a> {
 
     /// Get the next utf8 string from the stream
     pub fn utf8_string(&mut self) -> Result<&'a str, Error> {
-        let len = self.next_assume(PC::Primitive, constants::TAG_OCTET_STRING)?;
+        let len = self.next_assume(PC::Primitive, constants::TAG_UTF8_STRING)?;
         let sub = self.subslice(len)?;
         core::str::from_utf8(sub).map_err(|_| Error::Utf8Invalid)
     }
 
+    reader_string!(
+        numeric_string,
+        optional_numeric_string,
+        implicit_numeric_string,
+        NumericString,
+        TAG_NUMERIC_STRING,
+        NumericStringInvalid,
+        "numeric string"
+    );
+    reader_string!(
+        printable_string,
+        optional_printable_string,
+        implicit_printable_string,
+        PrintableString,
+        TAG_PRINTABLE_STRING,
+        PrintableStringInvalid,
+        "printable string"
+    );
+    reader_string!(
+        ia5_string,
+        optional_ia5_string,
+        implicit_ia5_string,
+        IA5String,
+        TAG_IA5_STRING,
+        IA5StringInvalid,
+        "IA5 string"
+    );
+    reader_string!(
+        visible_string,
+        optional_visible_string,
+        implicit_visible_string,
+        VisibleString,
+        TAG_VISIBLE_STRING,
+        VisibleStringInvalid,
+        "visible string"
+    );
+    reader_string!(
+        universal_string,
+        optional_universal_string,
+        implicit_universal_string,
+        UniversalString,
+        TAG_UNIVERSAL_STRING,
+        UniversalStringInvalid,
+        "universal string"
+    );
+    reader_string!(
+        bmp_string,
+        optional_bmp_string,
+        implicit_bmp_string,
+        BMPString,
+        TAG_BMP_STRING,
+        BMPStringInvalid,
+        "BMP string"
+    );
+
+    /// Get the next T61 string from the stream
+    pub fn t61_string(&mut self) -> Result<&'a T61String, Error> {
+        let len = self.next_assume(PC::Primitive, constants::TAG_T61_STRING)?;
+        let sub = self.subslice(len)?;
+        Ok(T61String::from_slice(sub))
+    }
+
+    reader_optional!(
+        optional_t61_string,
+        t61_string,
+        &'a T61String,
+        TAG_T61_STRING,
+        "T61 string"
+    );
+    reader_implicit!(implicit_t61_string, t61_string, &'a T61String, "T61 string");
+
+    /// Get the next string from the stream, whichever character string type it is
+    ///
+    /// When the next element is not a string, the error is the one of reading a utf8 string
+    pub fn any_string(&mut self) -> Result<AnyString<'a>, Error> {
+        let tag = match self.peek_identifier()? {
+            Some(ident) if ident.class == Class::Universal => ident.tag.value(),
+            _ => constants::TAG_UTF8_STRING,
+        };
+        match tag {
+            constants::TAG_NUMERIC_STRING => self.numeric_string().map(AnyString::Numeric),
+            constants::TAG_PRINTABLE_STRING => self.printable_string().map(AnyString::Printable),
+            constants::TAG_T61_STRING => self.t61_string().map(AnyString::T61),
+            constants::TAG_IA5_STRING => self.ia5_string().map(AnyString::IA5),
+            constants::TAG_VISIBLE_STRING => self.visible_string().map(AnyString::Visible),
+            constants::TAG_UNIVERSAL_STRING => self.universal_string().map(AnyString::Universal),
+            constants::TAG_BMP_STRING => self.bmp_string().map(AnyString::BMP),
+            _ => self.utf8_string().map(AnyString::Utf8),
+        }
+    }
+
     /// Get the next null from the stream
     pub fn null(&mut self) -> Result<(), Error> {
         let len = self.next_assume(PC::Primitive, constants::TAG_NULL)?;
@@ -206,6 +752,38 @@ impl<'a> Reader<'a> {
         OID::parse_from_slice(sub).map_err(|_| Error::OIDInvalid)
     }
 
+    /// Get the next real from the stream, which need to be in the canonical DER encoding
+    pub fn real(&mut self) -> Result<&'a Real, Error> {
+        let len = self.next_assume(PC::Primitive, constants::TAG_REAL)?;
+        let sub = self.subslice(len)?;
+        let real = Real::from_slice(sub).map_err(|_| Error::RealInvalid)?;
+        if !real.is_der_canonical() {
+            return Err(Error::RealNotCanonical);
+        }
+        Ok(real)
+    }
+
+    /// Get the next UTC time from the stream
+    pub fn utc_time(&mut self) -> Result<&'a UtcTime, Error> {
+        let len = self.next_assume(PC::Primitive, constants::TAG_UTC_TIME)?;
+        let sub = self.subslice(len)?;
+        UtcTime::from_slice(sub).map_err(|_| Error::UtcTimeInvalid)
+    }
+
+    /// Get the next generalized time from the stream
+    pub fn generalized_time(&mut self) -> Result<&'a GeneralizedTime, Error> {
+        let len = self.next_assume(PC::Primitive, constants::TAG_GENERALIZED_TIME)?;
+        let sub = self.subslice(len)?;
+        GeneralizedTime::from_slice(sub).map_err(|_| Error::GeneralizedTimeInvalid)
+    }
+
+    /// Get the next relative OID from the stream
+    pub fn relative_oid(&mut self) -> Result<&'a RelativeOID, Error> {
+        let len = self.next_assume(PC::Primitive, constants::TAG_RELATIVE_OID)?;
+        let sub = self.subslice(len)?;
+        RelativeOID::parse_from_slice(sub).map_err(|_| Error::RelativeOIDInvalid)
+    }
+
     /// Get the next sequence from the stream as a Reader
     pub fn sequence(&mut self) -> Result<Reader<'a>, Error> {
         let len = self.next_assume(PC::Constructed, constants::TAG_SEQUENCE)?;
@@ -215,13 +793,124 @@ impl<'a> Reader<'a> {
     /// Get the next set from the stream as a Set iterator
     pub fn set<A, F>(&mut self, f: F) -> Result<Set<'a, F>, Error>
     where
-        F: Fn(Reader<'a>) -> Result<A, Error>,
+        F: Fn(&mut Reader<'a>) -> Result<A, Error>,
     {
         let len = self.next_assume(PC::Constructed, constants::TAG_SET)?;
         let subreader = self.subslice_reader(len)?;
         Ok(Set(subreader, f))
     }
 
+    /// Get the next context specific EXPLICIT tagged element from the stream,
+    /// as a Reader over the inner element
+    pub fn explicit(&mut self, tag: u32) -> Result<Reader<'a>, Error> {
+        let len = self.next_assume_class(Class::Context, PC::Constructed, tag)?;
+        self.subslice_reader(len)
+    }
+
+    // read an element with f, where its universal tag is replaced
+    // by a context specific IMPLICIT tag
+    fn with_implicit<T, F>(&mut self, tag: u32, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<T, Error>,
+    {
+        self.implicit = Some((Class::Context, tag));
+        let r = f(self);
+        self.implicit = None;
+        r
+    }
+
+    reader_implicit!(implicit_bool, bool, bool, "boolean");
+    reader_implicit!(implicit_integer, integer, &'a Integer, "integer");
+    reader_implicit!(
+        implicit_enumerated,
+        enumerated,
+        &'a Enumerated,
+        "enumerated"
+    );
+    reader_implicit!(implicit_bitstring, bitstring, &'a BitString, "bitstring");
+    reader_implicit!(implicit_octetstring, octetstring, &'a [u8], "octetstring");
+    reader_implicit!(implicit_utf8_string, utf8_string, &'a str, "utf8 string");
+    reader_implicit!(implicit_null, null, (), "null");
+    reader_implicit!(implicit_oid, oid, &'a OID, "OID");
+    reader_implicit!(implicit_real, real, &'a Real, "real");
+    reader_implicit!(implicit_utc_time, utc_time, &'a UtcTime, "UTC time");
+    reader_implicit!(
+        implicit_generalized_time,
+        generalized_time,
+        &'a GeneralizedTime,
+        "generalized time"
+    );
+    reader_implicit!(
+        implicit_relative_oid,
+        relative_oid,
+        &'a RelativeOID,
+        "relative OID"
+    );
+    reader_implicit!(implicit_sequence, sequence, Reader<'a>, "sequence");
+
+    /// Get the next decodable value from the stream
+    pub fn decode<T: DerDecode<'a>>(&mut self) -> Result<T, Error> {
+        T::decode(self)
+    }
+
+    /// Get the next decodable value from the stream, wrapped in a context specific
+    /// EXPLICIT tag
+    pub fn decode_explicit<T: DerDecode<'a>>(&mut self, tag: u32) -> Result<T, Error> {
+        let mut inner = self.explicit(tag)?;
+        let v = T::decode(&mut inner)?;
+        inner.done()?;
+        Ok(v)
+    }
+
+    /// Get the next decodable value from the stream, where its tag
+    /// is replaced by a context specific IMPLICIT tag
+    pub fn decode_implicit<T: DerDecode<'a>>(&mut self, tag: u32) -> Result<T, Error> {
+        self.with_implicit(tag, T::decode)
+    }
+
+    /// Get the next decodable value from the stream, if present
+    ///
+    /// The element is present if its identifier is one of a `T`, as told by
+    /// `DerDecode::can_decode`, and any error decoding it is then returned.
+    /// Nothing is consumed from the stream if the element is not present
+    pub fn optional<T: DerDecode<'a>>(&mut self) -> Result<Option<T>, Error> {
+        match self.peek_identifier()? {
+            Some(hdr) if T::can_decode(&hdr) => T::decode(self).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Read the next element with f, which return nothing when the element is absent,
+    /// in which case it has the DEFAULT value
+    ///
+    /// As DER forbids encoding the DEFAULT value, Error::DefaultValueEncoded is
+    /// returned if the element is present with this value
+    pub fn with_default<T, F>(&mut self, default: T, f: F) -> Result<T, Error>
+    where
+        T: PartialEq,
+        F: FnOnce(&mut Self) -> Result<Option<T>, Error>,
+    {
+        let offset = self.position();
+        match f(self)? {
+            None => Ok(default),
+            Some(v) if v == default => Err(Error::DefaultValueEncoded { offset }),
+            Some(v) => Ok(v),
+        }
+    }
+
+    /// Read the next element as one of the alternatives of a CHOICE
+    ///
+    /// f is given the identifier of the next element, and returns None if it's
+    /// none of the alternatives, in which case Error::ChoiceInvalid is returned
+    pub fn choice<T, F>(&mut self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut Self, &Identifier) -> Option<Result<T, Error>>,
+    {
+        let offset = self.position();
+        let (hdr, _) = self.decode_identifier()?;
+        f(self, &hdr).unwrap_or(Err(Error::ChoiceInvalid { offset }))
+    }
+
     /// Check is the stream is done
     ///
     /// If the reader is not finished, then the Error::ReaderNotTerminated is returned
@@ -250,6 +939,7 @@ impl<'a> Reader<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::string::ToString;
     use alloc::vec::Vec;
 
     #[test]
@@ -287,4 +977,390 @@ mod tests {
         let bits = out_sequence.bitstring().expect("bitstring");
         assert_eq!(bits.bits(), 520);
     }
+
+    #[test]
+    pub fn decode_truncated() {
+        // empty stream
+        let mut reader = Reader::new(&b""[..]);
+        match reader.octetstring() {
+            Err(Error::IdentifierTruncated { offset: 0 }) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+
+        // long tag form without the tag bytes
+        let mut reader = Reader::new(&b"\x1f\x81"[..]);
+        match reader.anything() {
+            Err(Error::IdentifierTruncated { offset: 0 }) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+
+        // missing length
+        let mut reader = Reader::new(&b"\x04"[..]);
+        match reader.octetstring() {
+            Err(Error::LengthTruncated { offset: 1 }) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+
+        // long length form missing bytes
+        let mut reader = Reader::new(&b"\x04\x82\x01"[..]);
+        match reader.octetstring() {
+            Err(Error::LengthTruncated { offset: 1 }) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+
+        // length overflowing 32 bits
+        let mut reader = Reader::new(&b"\x04\x85\x01\x00\x00\x00\x00"[..]);
+        match reader.octetstring() {
+            Err(Error::LengthInvalid { offset: 1 }) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+
+        // content longer than the stream
+        let mut reader = Reader::new(&b"\x04\x08\x01\x23"[..]);
+        match reader.octetstring() {
+            Err(Error::DataTruncated {
+                offset: 2,
+                length: 8,
+                remaining: 2,
+            }) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+    }
+
+    #[test]
+    pub fn decode_truncated_nested_offset() {
+        // sequence { bool, octetstring with a length larger than the sequence }
+        let mut reader = Reader::new(&b"\x30\x06\x01\x01\xff\x04\x05\x00"[..]);
+        let mut seq = reader.sequence().expect("sequence");
+        assert!(seq.bool().expect("bool"));
+        match seq.octetstring() {
+            Err(Error::DataTruncated {
+                offset: 7,
+                length: 5,
+                remaining: 1,
+            }) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+    }
+
+    #[test]
+    pub fn decode_length_not_canonical() {
+        let mut reader = Reader::new(&b"\x05\x81\x00"[..]);
+        match reader.null() {
+            Err(Error::LengthNotCanonical { offset: 1 }) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+        let mut reader = Reader::new(&b"\x30\x04\x04\x81\x01\x00"[..]);
+        let mut seq = reader.sequence().expect("sequence");
+        match seq.octetstring() {
+            Err(Error::LengthNotCanonical { offset: 3 }) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+    }
+
+    #[test]
+    pub fn decode_signed_integers() {
+        let data = b"\x02\x01\x00\x02\x02\x00\x80\x02\x01\x80\x02\x02\xfe\xff";
+        let mut reader = Reader::new(&data[..]);
+        assert_eq!(reader.integer().expect("zero").to_u64(), Some(0));
+        let v = reader.integer().expect("positive");
+        assert!(!v.is_negative());
+        assert_eq!(v.to_u8(), Some(0x80));
+        let v = reader.integer().expect("negative");
+        assert!(v.is_negative());
+        assert_eq!(v.to_u8(), None);
+        assert_eq!(v.to_i8(), Some(-128));
+        assert_eq!(reader.integer().expect("negative").to_i32(), Some(-257));
+        reader.done().expect("done");
+
+        for data in &[&b"\x02\x02\x00\x7f"[..], b"\x02\x02\xff\x80", b"\x02\x00"] {
+            match Reader::new(data).integer() {
+                Err(Error::IntegerNotCanonical) => {}
+                r => panic!("unexpected result {:?}", r),
+            }
+        }
+    }
+
+    #[test]
+    pub fn decode_context_tags() {
+        // sequence { [0] EXPLICIT INTEGER, [2] IMPLICIT INTEGER, [3] IMPLICIT SEQUENCE { bool } }
+        let data = b"\x30\x0d\xa0\x03\x02\x01\x02\x82\x01\x05\xa3\x03\x01\x01\xff";
+        let mut reader = Reader::new(&data[..]);
+        let mut seq = reader.sequence().expect("sequence");
+        let mut version = seq.explicit(0).expect("explicit");
+        assert_eq!(version.integer().expect("version").to_u8(), Some(2));
+        version.done().expect("explicit done");
+        assert_eq!(seq.implicit_integer(2).expect("implicit").to_u8(), Some(5));
+        let mut inner = seq.implicit_sequence(3).expect("implicit sequence");
+        assert!(inner.bool().expect("bool"));
+        inner.done().expect("inner done");
+        seq.done().expect("done");
+
+        // wrong tag, or universal where context is expected
+        let mut reader = Reader::new(&b"\x82\x01\x05"[..]);
+        match reader.clone().implicit_integer(1) {
+            Err(Error::ExpectedTag {
+                expected: 1,
+                got: 2,
+            }) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+        match reader.integer() {
+            Err(Error::ExpectedClass {
+                expected: Class::Universal,
+                got: Class::Context,
+            }) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+        let mut reader = Reader::new(&b"\x02\x01\x05"[..]);
+        match reader.implicit_integer(2) {
+            Err(Error::ExpectedClass {
+                expected: Class::Context,
+                got: Class::Universal,
+            }) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+    }
+
+    #[test]
+    pub fn decode_validity() {
+        let data = b"\x30\x1e\x17\x0d\x32\x30\x30\x31\x30\x31\x30\x30\x30\x30\x30\x30\x5a\x18\x0d\x32\x30\x35\x30\x30\x31\x30\x31\x30\x30\x30\x30\x5a";
+        let mut reader = Reader::new(&data[..]);
+        assert!(reader
+            .clone()
+            .sequence()
+            .unwrap()
+            .generalized_time()
+            .is_err());
+        let mut validity = reader.sequence().expect("validity");
+        let not_before = validity.utc_time().expect("not before");
+        assert_eq!(not_before.to_unix_timestamp(), 1577836800);
+        match validity.generalized_time() {
+            Err(Error::GeneralizedTimeInvalid) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    pub fn decode_restricted_strings() {
+        // SEQUENCE { printable "US", ia5 "a@b", bmp "é" }
+        let data = b"\x30\x0d\x13\x02US\x16\x03a@b\x1e\x02\x00\xe9";
+        let mut reader = Reader::new(&data[..]);
+        let mut seq = reader.sequence().expect("sequence");
+        assert_eq!(seq.printable_string().expect("printable").as_str(), "US");
+        assert!(seq.clone().printable_string().is_err());
+        assert_eq!(
+            seq.optional_ia5_string().expect("ia5").map(|s| s.as_str()),
+            Some("a@b")
+        );
+        assert_eq!(seq.bmp_string().expect("bmp").chars().next(), Some('é'));
+        seq.done().expect("done");
+
+        match Reader::new(&b"\x13\x01@"[..]).printable_string() {
+            Err(Error::PrintableStringInvalid) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    pub fn decode_any_string() {
+        let data = b"\x0c\x03\xc3\xa9a\x13\x02CA\x1e\x02\x00\xe9\x04\x01a";
+        let mut reader = Reader::new(&data[..]);
+        assert!(reader.clone().octetstring().is_err());
+        assert_eq!(reader.any_string().expect("utf8"), AnyString::Utf8("éa"));
+        assert_eq!(reader.any_string().expect("printable").as_str(), Some("CA"));
+        assert_eq!(reader.any_string().expect("bmp").to_string(), "é");
+        match reader.clone().any_string() {
+            Err(Error::ExpectedTag {
+                expected: 0xc,
+                got: 0x4,
+            }) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+        // an octet string is not an utf8 string
+        match reader.utf8_string() {
+            Err(Error::ExpectedTag {
+                expected: 0xc,
+                got: 0x4,
+            }) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    pub fn decode_real() {
+        let data = b"\x09\x03\x80\xff\x01\x09\x00\x09\x01\x40\x09\x03\x80\x00\x02\x09\x01\x44";
+        let mut reader = Reader::new(&data[..]);
+        assert_eq!(reader.real().expect("real").to_f64(), Some(0.5));
+        assert_eq!(reader.real().expect("zero").to_f64(), Some(0.0));
+        assert_eq!(
+            reader
+                .optional_real()
+                .expect("inf")
+                .and_then(|r| r.to_f64()),
+            Some(f64::INFINITY)
+        );
+        match reader.real() {
+            Err(Error::RealNotCanonical) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+        match reader.real() {
+            Err(Error::RealInvalid) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    pub fn decode_any_raw() {
+        // SEQUENCE { tbs SEQUENCE { INTEGER 1, BOOLEAN true }, BIT STRING }
+        let data = b"\x30\x0c\x30\x06\x02\x01\x01\x01\x01\xff\x03\x02\x00\xaa";
+        let mut reader = Reader::new(&data[..]);
+        let mut seq = reader.sequence().expect("sequence");
+        let tbs = seq.any_raw().expect("tbs");
+        assert_eq!(tbs.as_bytes(), &data[2..10]);
+        assert_eq!(tbs.content(), &data[4..10]);
+        assert_eq!(tbs.identifier().tag.value(), constants::TAG_SEQUENCE);
+        assert_eq!(tbs.length().value(), Some(6));
+        let (version, flag): (&Integer, bool) = tbs.decode_as().expect("decode tbs");
+        assert_eq!(version.to_u8(), Some(1));
+        assert!(flag);
+        assert!(tbs.decode_as::<bool>().is_err());
+        seq.bitstring().expect("bitstring");
+        seq.done().expect("done");
+
+        assert!(Reader::new(&data[..2]).any_raw().is_err());
+    }
+
+    #[test]
+    pub fn error_display() {
+        let mut reader = Reader::new(&b"\x02\x02\x00\x01"[..]);
+        let e = reader.integer().unwrap_err();
+        assert_eq!(e.to_string(), "INTEGER not in minimal encoding");
+
+        let mut reader = Reader::new(&b"\x04\x05ab"[..]);
+        let e = reader.octetstring().unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "content at offset 2 needs 5 bytes but only 2 remaining"
+        );
+        #[cfg(feature = "std")]
+        {
+            let e: std::boxed::Box<dyn std::error::Error> = e.into();
+            assert!(e.source().is_none());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    pub fn decode_limits() {
+        // 3 nested sequences around a boolean
+        let data = b"\x30\x07\x30\x05\x30\x03\x01\x01\xff";
+        let read = |config: ReaderConfig| -> Result<(bool, usize), Error> {
+            let mut reader = Reader::with_config(&data[..], config);
+            let mut s1 = reader.sequence()?;
+            let mut s2 = s1.sequence()?;
+            let mut s3 = s2.sequence()?;
+            s3.optional_integer()?;
+            let b = s3.bool()?;
+            Ok((b, reader.elements_read()))
+        };
+        let config = ReaderConfig::new(3, 16, 4);
+        assert_eq!(read(config).expect("within limits"), (true, 4));
+        // every reader has its own count
+        assert_eq!(read(config).expect("reused"), (true, 4));
+
+        for (config, kind, offset) in [
+            (ReaderConfig::new(2, 16, 4), LimitKind::Depth, 6),
+            (ReaderConfig::new(3, 6, 4), LimitKind::ElementSize, 0),
+            (ReaderConfig::new(3, 16, 3), LimitKind::Elements, 6),
+        ]
+        .iter()
+        {
+            match read(*config) {
+                Err(Error::LimitExceeded { kind: k, offset: o }) => {
+                    assert_eq!((k, o), (*kind, *offset))
+                }
+                r => panic!("unexpected result {:?}", r),
+            }
+        }
+
+        assert_eq!(read(ReaderConfig::default()).expect("no limits"), (true, 4));
+        assert_eq!(Reader::new(&data[..]).elements_read(), 0);
+
+        // the values read don't borrow the configuration
+        fn first_element(data: &[u8]) -> Result<Tlv<'_>, Error> {
+            let config = ReaderConfig::new(1, 8, 1);
+            Reader::with_config(data, config).any_raw()
+        }
+        assert_eq!(first_element(&data[6..]).expect("bool").content(), &[0xff]);
+
+        // a Tlv is decoded with the limits given, if any
+        let tlv = Reader::with_config(&data[..], config)
+            .any_raw()
+            .expect("tlv");
+        type Nested = (((bool,),),);
+        assert_eq!(tlv.decode_as::<Nested>().expect("no limits"), (((true,),),));
+        match tlv.decode_as_with::<Nested>(ReaderConfig::new(2, 16, 4)) {
+            Err(Error::LimitExceeded { kind, offset }) => {
+                assert_eq!((kind, offset), (LimitKind::Depth, 6))
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    pub fn decode_relative_oid() {
+        let data = b"\x0d\x03\x08\x86\x48\x80\x01\x05\x0d\x00";
+        let mut reader = Reader::new(&data[..]);
+        let rel = reader.relative_oid().expect("relative oid");
+        let components: Vec<u64> = rel.components().map(|c| c.to_u64().unwrap()).collect();
+        assert_eq!(&components, &[8, 840]);
+        let rel = reader
+            .implicit_relative_oid(0)
+            .expect("implicit relative oid");
+        assert_eq!(rel.as_ref(), &[0x05]);
+        match reader.relative_oid() {
+            Err(Error::RelativeOIDInvalid) => {}
+            r => panic!("unexpected result {:?}", r),
+        }
+    }
+
+    #[test]
+    pub fn decode_optional_fields() {
+        // sequence { INTEGER, [1] IMPLICIT BIT STRING OPTIONAL, [2] IMPLICIT BIT STRING OPTIONAL,
+        //            [3] EXPLICIT SEQUENCE OPTIONAL, BOOLEAN OPTIONAL }
+        let data = b"\x30\x0f\x02\x01\x01\x82\x02\x00\xff\xa3\x02\x30\x00\x01\x02\xff\xff";
+        let mut reader = Reader::new(&data[..]);
+        let mut seq = reader.sequence().expect("sequence");
+        assert_eq!(seq.optional_bool().expect("optional bool"), None);
+        assert!(seq.optional_integer().expect("integer").is_some());
+        let issuer_id = seq
+            .if_tag(Class::Context, 1, |r| r.implicit_bitstring(1))
+            .expect("issuer id");
+        assert!(issuer_id.is_none());
+        let subject_id = seq
+            .if_tag(Class::Context, 2, |r| r.implicit_bitstring(2))
+            .expect("subject id");
+        assert_eq!(subject_id.map(|b| b.bits()), Some(8));
+        let peeked = seq.peek_identifier().expect("peek").expect("not done");
+        assert_eq!(peeked.class, Class::Context);
+        assert_eq!(peeked.tag.value(), 3);
+        let extensions = seq.optional_explicit(3).expect("extensions");
+        assert!(extensions.is_some());
+        // malformed content is reported even if optional
+        match seq.optional_bool() {
+            Err(Error::BoolLengthInvalid(2)) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+
+        let data = b"\x30\x0b\x02\x01\x01\x82\x02\x00\xff\xa3\x02\x30\x00";
+        let mut reader = Reader::new(&data[..]);
+        let mut seq = reader.sequence().expect("sequence");
+        seq.integer().expect("integer");
+        seq.anything().expect("subject id");
+        seq.anything().expect("extensions");
+        assert_eq!(seq.peek_identifier().expect("peek"), None);
+        assert_eq!(seq.optional_bool().expect("done"), None);
+        assert!(seq.optional_explicit(3).expect("done").is_none());
+    }
 }
@@ -0,0 +1,413 @@
+//! DER Writer writing from the end of the buffer
+//!
+//! The content of an element is written before its header, so the
+//! length is always known when the header is written, and the data
+//! is never moved, whatever the size of the constructed elements.
+//!
+//! The consequence is that the elements need to be written in reverse
+//! order: the last element of a sequence is written first.
+use super::tlv::Tlv;
+use super::writer::{sort_elements, tag_order};
+use crate::header::*;
+use crate::intenc::Integer8Bit;
+use crate::objects::*;
+
+pub use super::writer::Error;
+
+/// ASN.1 DER Writer to a mutable buffer, from the end of the buffer
+pub struct ReverseWriter<'a> {
+    // the encoding is written from the end, and start at index
+    index: usize,
+    buf: &'a mut [u8],
+    // class and tag replacing the universal tag of the next element,
+    // when writing an IMPLICIT tagged element
+    implicit: Option<(Class, u32)>,
+}
+
+impl<'a> ReverseWriter<'a> {
+    /// create a new DER reverse writer, with the buffer as the user allocated write buffer
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        ReverseWriter {
+            index: buf.len(),
+            buf,
+            implicit: None,
+        }
+    }
+
+    // write the bytes before the already written data
+    fn bytes(&mut self, data: &[u8]) -> Result<(), Error> {
+        if data.len() > self.index {
+            return Err(Error::BufferTooSmall(self.buf.len()));
+        }
+        self.index -= data.len();
+        self.buf[self.index..self.index + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    // an IMPLICIT tag being set replace the class and tag, and need to be taken
+    // before the content is written, as the content may contain other elements
+    fn tag(&mut self, class: Class, tag: u32) -> (Class, u32) {
+        self.implicit.take().unwrap_or((class, tag))
+    }
+
+    // write the header of an element with the content of length bytes already written
+    fn header(&mut self, class: Class, pc: PC, tag: u32, length: usize) -> Result<(), Error> {
+        let mut out = [0u8; 8];
+        let length = Length::new_smallest(length);
+        length.encode(&mut out);
+        self.bytes(&out[..length.size_bytes()])?;
+        let ident = Identifier {
+            pc,
+            class,
+            tag: TagEncoded::new_smallest(tag),
+        };
+        let sz = ident.encode(&mut out);
+        self.bytes(&out[..sz])
+    }
+
+    /// write a primitive universal element with the given content
+    pub(crate) fn primitive(&mut self, tag: u32, data: &[u8]) -> Result<(), Error> {
+        let (class, tag) = self.tag(Class::Universal, tag);
+        self.bytes(data)?;
+        self.header(class, PC::Primitive, tag, data.len())
+    }
+
+    fn constructed<F, R>(&mut self, class: Class, tag: u32, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<R, Error>,
+    {
+        let (class, tag) = self.tag(class, tag);
+        let end = self.index;
+        let r = f(self)?;
+        self.header(class, PC::Constructed, tag, end - self.index)?;
+        Ok(r)
+    }
+
+    /// Write a boolean to the DER reverse writer
+    pub fn bool(&mut self, b: bool) -> Result<(), Error> {
+        let v = if b { [0xff] } else { [0] };
+        self.primitive(constants::TAG_BOOLEAN, &v)
+    }
+
+    /// Write an Integer to the DER reverse writer
+    pub fn integer(&mut self, integer: &Integer) -> Result<(), Error> {
+        self.primitive(constants::TAG_INTEGER, integer.as_ref())
+    }
+
+    /// Write a signed primitive as an Integer to the DER reverse writer
+    pub fn integer_i64(&mut self, v: i64) -> Result<(), Error> {
+        let mut buf = [0u8; 9];
+        let integer = Integer8Bit::encode_i64(v, &mut buf);
+        self.primitive(constants::TAG_INTEGER, integer.as_ref())
+    }
+
+    /// Write an unsigned primitive as an Integer to the DER reverse writer
+    pub fn integer_u64(&mut self, v: u64) -> Result<(), Error> {
+        let mut buf = [0u8; 9];
+        let integer = Integer8Bit::encode_u64(v, &mut buf);
+        self.primitive(constants::TAG_INTEGER, integer.as_ref())
+    }
+
+    /// Write an Enumerated to the DER reverse writer
+    pub fn enumerated(&mut self, enumerated: &Enumerated) -> Result<(), Error> {
+        self.primitive(constants::TAG_ENUMERATED, enumerated.as_ref())
+    }
+
+    /// Write a bitstring to the DER reverse writer
+    pub fn bitstring(&mut self, obj: &BitString) -> Result<(), Error> {
+        self.primitive(constants::TAG_BIT_STRING, obj.as_ref())
+    }
+
+    /// Write a octetstring to the DER reverse writer
+    pub fn octetstring(&mut self, obj: &[u8]) -> Result<(), Error> {
+        self.primitive(constants::TAG_OCTET_STRING, obj)
+    }
+
+    /// Write an OID to the DER reverse writer
+    pub fn oid(&mut self, oid: &OID) -> Result<(), Error> {
+        self.primitive(constants::TAG_OID, oid.as_ref())
+    }
+
+    /// Write a relative OID to the DER reverse writer
+    pub fn relative_oid(&mut self, oid: &RelativeOID) -> Result<(), Error> {
+        self.primitive(constants::TAG_RELATIVE_OID, oid.as_ref())
+    }
+
+    /// Write a real to the DER reverse writer
+    pub fn real(&mut self, real: &Real) -> Result<(), Error> {
+        self.primitive(constants::TAG_REAL, real.as_ref())
+    }
+
+    /// Write a f64 as a real to the DER reverse writer
+    pub fn real_f64(&mut self, v: f64) -> Result<(), Error> {
+        let mut buf = [0u8; 10];
+        let real = Real::from_f64(v, &mut buf);
+        self.real(real)
+    }
+
+    /// Write a UTC time to the DER reverse writer
+    pub fn utc_time(&mut self, time: &UtcTime) -> Result<(), Error> {
+        self.primitive(constants::TAG_UTC_TIME, time.as_ref())
+    }
+
+    /// Write a generalized time to the DER reverse writer
+    pub fn generalized_time(&mut self, time: &GeneralizedTime) -> Result<(), Error> {
+        self.primitive(constants::TAG_GENERALIZED_TIME, time.as_ref())
+    }
+
+    /// Write a null to the DER reverse writer
+    pub fn null(&mut self) -> Result<(), Error> {
+        self.primitive(constants::TAG_NULL, &[])
+    }
+
+    /// Write a utf8 string to the DER reverse writer
+    pub fn utf8_string(&mut self, str: &str) -> Result<(), Error> {
+        self.primitive(constants::TAG_UTF8_STRING, str.as_bytes())
+    }
+
+    writer_string!(
+        numeric_string,
+        implicit_numeric_string,
+        NumericString,
+        TAG_NUMERIC_STRING,
+        "numeric string"
+    );
+    writer_string!(
+        printable_string,
+        implicit_printable_string,
+        PrintableString,
+        TAG_PRINTABLE_STRING,
+        "printable string"
+    );
+    writer_string!(
+        t61_string,
+        implicit_t61_string,
+        T61String,
+        TAG_T61_STRING,
+        "T61 string"
+    );
+    writer_string!(
+        ia5_string,
+        implicit_ia5_string,
+        IA5String,
+        TAG_IA5_STRING,
+        "IA5 string"
+    );
+    writer_string!(
+        visible_string,
+        implicit_visible_string,
+        VisibleString,
+        TAG_VISIBLE_STRING,
+        "visible string"
+    );
+    writer_string!(
+        universal_string,
+        implicit_universal_string,
+        UniversalString,
+        TAG_UNIVERSAL_STRING,
+        "universal string"
+    );
+    writer_string!(
+        bmp_string,
+        implicit_bmp_string,
+        BMPString,
+        TAG_BMP_STRING,
+        "BMP string"
+    );
+
+    /// Write any string to the DER reverse writer, with the tag of its character string type
+    pub fn any_string(&mut self, string: &AnyString<'_>) -> Result<(), Error> {
+        match string {
+            AnyString::Utf8(s) => self.utf8_string(s),
+            AnyString::Numeric(s) => self.numeric_string(s),
+            AnyString::Printable(s) => self.printable_string(s),
+            AnyString::T61(s) => self.t61_string(s),
+            AnyString::IA5(s) => self.ia5_string(s),
+            AnyString::Visible(s) => self.visible_string(s),
+            AnyString::Universal(s) => self.universal_string(s),
+            AnyString::BMP(s) => self.bmp_string(s),
+        }
+    }
+
+    /// Write an element verbatim from its full encoding
+    pub fn raw_tlv(&mut self, tlv: &Tlv<'_>) -> Result<(), Error> {
+        self.bytes(tlv.as_bytes())
+    }
+
+    /// Write a sequence to the DER reverse writer,
+    /// where f writes the elements of the sequence from the last one to the first one
+    pub fn sequence<F, R>(&mut self, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<R, Error>,
+    {
+        self.constructed(Class::Universal, constants::TAG_SEQUENCE, f)
+    }
+
+    /// Write a set to the DER reverse writer
+    ///
+    /// The elements written by f are sorted in the canonical order of a SET, by
+    /// their class and then their tag number, so they can be written in any order.
+    pub fn set<F, R>(&mut self, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<R, Error>,
+    {
+        self.constructed(Class::Universal, constants::TAG_SET, |w| {
+            let end = w.index;
+            let r = f(w)?;
+            sort_elements(&mut w.buf[w.index..end], tag_order);
+            Ok(r)
+        })
+    }
+
+    /// Write an EXPLICIT tagged element to the DER reverse writer,
+    /// where the inner element is written by f
+    pub fn explicit<F, R>(&mut self, class: Class, tag: u32, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<R, Error>,
+    {
+        self.constructed(class, tag, f)
+    }
+
+    // write an element with f, where its universal tag is replaced by an IMPLICIT tag
+    fn with_implicit<F, R>(&mut self, class: Class, tag: u32, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<R, Error>,
+    {
+        self.implicit = Some((class, tag));
+        let r = f(self);
+        self.implicit = None;
+        r
+    }
+
+    writer_implicit!(implicit_bool, bool, bool, "boolean");
+    writer_implicit!(implicit_integer, integer, &Integer, "integer");
+    writer_implicit!(implicit_enumerated, enumerated, &Enumerated, "enumerated");
+    writer_implicit!(implicit_bitstring, bitstring, &BitString, "bitstring");
+    writer_implicit!(implicit_octetstring, octetstring, &[u8], "octetstring");
+    writer_implicit!(implicit_utf8_string, utf8_string, &str, "utf8 string");
+    writer_implicit!(implicit_oid, oid, &OID, "OID");
+    writer_implicit!(implicit_real, real, &Real, "real");
+    writer_implicit!(implicit_utc_time, utc_time, &UtcTime, "UTC time");
+    writer_implicit!(
+        implicit_generalized_time,
+        generalized_time,
+        &GeneralizedTime,
+        "generalized time"
+    );
+    writer_implicit!(
+        implicit_relative_oid,
+        relative_oid,
+        &RelativeOID,
+        "relative OID"
+    );
+
+    /// Write a null with an IMPLICIT tag to the DER reverse writer
+    pub fn implicit_null(&mut self, class: Class, tag: u32) -> Result<(), Error> {
+        self.with_implicit(class, tag, |w| w.null())
+    }
+
+    /// Write a sequence with an IMPLICIT tag to the DER reverse writer
+    pub fn implicit_sequence<F, R>(&mut self, class: Class, tag: u32, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<R, Error>,
+    {
+        self.with_implicit(class, tag, |w| w.sequence(f))
+    }
+
+    /// Get the number of bytes written
+    pub fn written(&self) -> usize {
+        self.buf.len() - self.index
+    }
+
+    /// Return the inner sub-slice, at the end of the buffer, with a valid DER stream of data
+    pub fn finish<'b: 'a>(&'b self) -> &'a [u8] {
+        &self.buf[self.index..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::der::Writer;
+
+    #[test]
+    pub fn encode_reverse_same_as_forward() {
+        let ostring = [2u8; 300];
+        let two = Integer::from_slice(&[2]).unwrap();
+
+        let mut buf = [0u8; 512];
+        let mut writer = Writer::new(&mut buf);
+        writer
+            .sequence(|w| {
+                w.explicit(Class::Context, 0, |w| w.integer(two))?;
+                w.sequence(|w| w.octetstring(&ostring))?;
+                w.implicit_bool(Class::Context, 1, true)?;
+                w.set(|w| {
+                    w.null()?;
+                    w.bool(false)
+                })
+            })
+            .expect("forward");
+        let expected = writer.finish();
+
+        let mut buf = [0u8; 512];
+        let mut writer = ReverseWriter::new(&mut buf);
+        writer
+            .sequence(|w| {
+                w.set(|w| {
+                    w.null()?;
+                    w.bool(false)
+                })?;
+                w.implicit_bool(Class::Context, 1, true)?;
+                w.sequence(|w| w.octetstring(&ostring))?;
+                w.explicit(Class::Context, 0, |w| w.integer(two))
+            })
+            .expect("reverse");
+        assert_eq!(writer.written(), expected.len());
+        assert_eq!(writer.finish(), expected);
+    }
+
+    #[test]
+    pub fn encode_reverse_set_same_as_forward() {
+        // ordered by class then tag number: the constructed [0] is before the
+        // primitive [1], while the order of the encodings would put it last
+        let mut buf = [0u8; 64];
+        let mut writer = Writer::new(&mut buf);
+        writer
+            .set(|w| {
+                w.implicit_bool(Class::Application, 2, true)?;
+                w.implicit_bool(Class::Context, 1, true)?;
+                w.explicit(Class::Context, 0, |w| w.null())?;
+                w.null()
+            })
+            .expect("forward");
+        let expected = writer.finish();
+        assert_eq!(
+            expected,
+            &b"\x31\x0c\x05\x00\x42\x01\xff\xa0\x02\x05\x00\x81\x01\xff"[..]
+        );
+
+        let mut buf = [0u8; 64];
+        let mut writer = ReverseWriter::new(&mut buf);
+        writer
+            .set(|w| {
+                w.implicit_bool(Class::Context, 1, true)?;
+                w.null()?;
+                w.explicit(Class::Context, 0, |w| w.null())?;
+                w.implicit_bool(Class::Application, 2, true)
+            })
+            .expect("reverse");
+        assert_eq!(writer.finish(), expected);
+    }
+
+    #[test]
+    pub fn encode_reverse_buffer_too_small() {
+        let mut buf = [0u8; 4];
+        let mut writer = ReverseWriter::new(&mut buf);
+        writer.bool(true).expect("bool");
+        assert!(matches!(
+            writer.sequence(|w| w.null()),
+            Err(Error::BufferTooSmall(4))
+        ));
+    }
+}
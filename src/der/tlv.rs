@@ -0,0 +1,76 @@
+//! Zero-copy view of a whole encoded element
+use super::codec::DerDecode;
+#[cfg(feature = "alloc")]
+use super::reader::ReaderConfig;
+use super::reader::{Error, Reader};
+use crate::header::{Identifier, Length};
+
+/// A DER element of any type, which retains its full encoding (identifier,
+/// length and content), so that it can be hashed or written again verbatim
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tlv<'a> {
+    identifier: Identifier,
+    length: Length,
+    raw: &'a [u8],
+    header_size: usize,
+}
+
+impl<'a> Tlv<'a> {
+    pub(crate) fn new(
+        identifier: Identifier,
+        length: Length,
+        raw: &'a [u8],
+        header_size: usize,
+    ) -> Self {
+        Tlv {
+            identifier,
+            length,
+            raw,
+            header_size,
+        }
+    }
+
+    /// Return the identifier of the element
+    pub fn identifier(&self) -> &Identifier {
+        &self.identifier
+    }
+
+    /// Return the length of the element
+    pub fn length(&self) -> Length {
+        self.length
+    }
+
+    /// Return the content of the element, without the identifier and the length
+    pub fn content(&self) -> &'a [u8] {
+        &self.raw[self.header_size..]
+    }
+
+    /// Return the full encoding of the element
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Decode the element as a T, which need to consume the whole element
+    ///
+    /// No limits apply, even if the element was read by a reader with a
+    /// configuration; use `decode_as_with` when the element is untrusted.
+    pub fn decode_as<T: DerDecode<'a>>(&self) -> Result<T, Error> {
+        Self::decode_with_reader(Reader::new(self.raw))
+    }
+
+    /// Decode the element as a T enforcing the limits of the configuration, which
+    /// need to consume the whole element
+    ///
+    /// The depth and the count of elements start afresh from the element, and errors
+    /// report offsets relative to the start of the element.
+    #[cfg(feature = "alloc")]
+    pub fn decode_as_with<T: DerDecode<'a>>(&self, config: ReaderConfig) -> Result<T, Error> {
+        Self::decode_with_reader(Reader::with_config(self.raw, config))
+    }
+
+    fn decode_with_reader<T: DerDecode<'a>>(mut reader: Reader<'a>) -> Result<T, Error> {
+        let v = reader.decode()?;
+        reader.done()?;
+        Ok(v)
+    }
+}
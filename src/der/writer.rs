@@ -1,73 +1,253 @@
+use super::codec::DerEncode;
+use super::tlv::Tlv;
 use crate::header::*;
+use crate::intenc::Integer8Bit;
 use crate::objects::*;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+
+macro_rules! writer_implicit {
+    ($name: ident, $write: ident, $ty: ty, $asn1: expr) => {
+        #[doc = concat!("Write a ", $asn1, " with an IMPLICIT tag to the DER writer")]
+        pub fn $name(&mut self, class: Class, tag: u32, value: $ty) -> Result<(), Error> {
+            self.with_implicit(class, tag, |w| w.$write(value))
+        }
+    };
+}
+
+// size of the element at the start of the slice, which has been written by the writer
+fn element_size(slice: &[u8]) -> usize {
+    let (_, ident_size) = Identifier::decode(slice).expect("valid identifier");
+    let (length, length_size) = Length::decode_der(&slice[ident_size..]).expect("valid length");
+    let content_size = length.value().expect("definite length") as usize;
+    ident_size + length_size + content_size
+}
+
+// order of the elements in a DER SET, by the class and then the number of
+// their tag, whether they are primitive or constructed
+pub(super) fn tag_order(a: &[u8], b: &[u8]) -> Ordering {
+    let key = |slice: &[u8]| {
+        let (ident, _) = Identifier::decode(slice).expect("valid identifier");
+        (ident.class as u8, ident.tag.value())
+    };
+    key(a).cmp(&key(b))
+}
+
+// order of the elements in a DER SET OF, which compare the encodings as
+// octet strings where the shorter one is padded with trailing zero octets
+pub(super) fn set_of_order(a: &[u8], b: &[u8]) -> Ordering {
+    let common = core::cmp::min(a.len(), b.len());
+    match a[..common].cmp(&b[..common]) {
+        Ordering::Equal => {}
+        o => return o,
+    }
+    if a[common..].iter().any(|x| *x != 0) {
+        Ordering::Greater
+    } else if b[common..].iter().any(|x| *x != 0) {
+        Ordering::Less
+    } else {
+        Ordering::Equal
+    }
+}
+
+// sort in place the elements of the slice in the given order, with a stable
+// insertion sort so that no extra memory is needed
+pub(super) fn sort_elements(slice: &mut [u8], order: fn(&[u8], &[u8]) -> Ordering) {
+    let end = slice.len();
+    let mut sorted_end = 0;
+    while sorted_end < end {
+        let size = element_size(&slice[sorted_end..end]);
+        let next = sorted_end + size;
+        let mut pos = 0;
+        while pos < sorted_end {
+            let current = pos + element_size(&slice[pos..sorted_end]);
+            if order(&slice[pos..current], &slice[sorted_end..next]) == Ordering::Greater {
+                break;
+            }
+            pos = current;
+        }
+        slice[pos..next].rotate_right(size);
+        sorted_end = next;
+    }
+}
+
+macro_rules! writer_string {
+    ($name: ident, $implicit: ident, $ty: ident, $tag: ident, $asn1: expr) => {
+        #[doc = concat!("Write a ", $asn1, " to the DER writer")]
+        pub fn $name(&mut self, string: &$ty) -> Result<(), Error> {
+            self.primitive(constants::$tag, string.as_ref())
+        }
+
+        writer_implicit!($implicit, $name, &$ty, $asn1);
+    };
+}
+
+// storage of the writer, either a fixed user allocated buffer,
+// a vector growing as needed, or nothing when only counting the size
+enum Buffer<'a> {
+    Slice(&'a mut [u8]),
+    #[cfg(feature = "alloc")]
+    Vec(Vec<u8>),
+    Count,
+    // counting the size, and recording the content lengths of the constructed
+    // elements in the order they are started
+    #[cfg(feature = "alloc")]
+    Record(Vec<usize>),
+    // a vector of the counted size, with the recorded lengths in reverse order
+    // so that the next one is popped from the end
+    #[cfg(feature = "alloc")]
+    Replay(Vec<u8>, Vec<usize>),
+}
+
+impl<'a> Deref for Buffer<'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Buffer::Slice(buf) => buf,
+            #[cfg(feature = "alloc")]
+            Buffer::Vec(v) | Buffer::Replay(v, _) => v,
+            Buffer::Count => &[],
+            #[cfg(feature = "alloc")]
+            Buffer::Record(_) => &[],
+        }
+    }
+}
+
+impl<'a> DerefMut for Buffer<'a> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        match self {
+            Buffer::Slice(buf) => buf,
+            #[cfg(feature = "alloc")]
+            Buffer::Vec(v) | Buffer::Replay(v, _) => v,
+            Buffer::Count => &mut [],
+            #[cfg(feature = "alloc")]
+            Buffer::Record(_) => &mut [],
+        }
+    }
+}
 
 /// ASN.1 DER Writer to a mutable buffer
 pub struct Writer<'a> {
     index: usize,
-    buf: &'a mut [u8],
+    buf: Buffer<'a>,
+    // class and tag replacing the universal tag of the next element,
+    // when writing an IMPLICIT tagged element
+    implicit: Option<(Class, u32)>,
 }
 
 /// ASN.1 DER Writer Error
 #[derive(Debug, Clone)]
 pub enum Error {
     BufferTooSmall(usize),
+    /// The value has not been encoded the same way when counting its size and when
+    /// writing it, so the recorded lengths don't match the content written
+    LengthMismatch,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::BufferTooSmall(len) => write!(f, "buffer of {} bytes too small", len),
+            Error::LengthMismatch => {
+                write!(
+                    f,
+                    "content changed between the size counting and the writing"
+                )
+            }
+        }
+    }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
 impl<'a> Writer<'a> {
     /// create a new DER writer, with the buffer as the user allocated write buffer
     pub fn new(buf: &'a mut [u8]) -> Self {
-        Writer { index: 0, buf }
+        Self::with_buffer(Buffer::Slice(buf))
     }
 
-    fn check_length(&self, sz: usize) -> Result<(), Error> {
-        if self.index + sz > self.buf.len() {
-            return Err(Error::BufferTooSmall(self.buf.len()));
+    fn with_buffer(buf: Buffer<'a>) -> Self {
+        Writer {
+            index: 0,
+            buf,
+            implicit: None,
         }
+    }
+
+    fn is_counting(&self) -> bool {
+        match self.buf {
+            Buffer::Count => true,
+            #[cfg(feature = "alloc")]
+            Buffer::Record(_) => true,
+            _ => false,
+        }
+    }
+
+    // make sure there's sz bytes available after the index, growing the vector if possible
+    fn check_length(&mut self, sz: usize) -> Result<(), Error> {
+        let needed = self.index + sz;
+        match &mut self.buf {
+            Buffer::Slice(buf) if needed > buf.len() => Err(Error::BufferTooSmall(buf.len())),
+            #[cfg(feature = "alloc")]
+            Buffer::Vec(v) | Buffer::Replay(v, _) if needed > v.len() => {
+                v.resize(needed, 0);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // write the bytes at the index, or only account for them when counting
+    fn bytes(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.check_length(data.len())?;
+        if !self.is_counting() {
+            self.buf[self.index..self.index + data.len()].copy_from_slice(data);
+        }
+        self.index += data.len();
         Ok(())
     }
 
     fn identifier(&mut self, identifier: &Identifier) -> Result<(), Error> {
-        let sz = identifier.size_bytes();
-        self.check_length(sz)?;
-        let sz2 = identifier.encode(&mut self.buf[self.index..]);
-        assert_eq!(sz, sz2);
-        self.index += sz;
-        Ok(())
+        let mut out = [0u8; 8];
+        let sz = identifier.encode(&mut out);
+        self.bytes(&out[..sz])
     }
 
     fn length(&mut self, length: Length) -> Result<(), Error> {
-        let sz = length.size_bytes();
-        self.check_length(sz)?;
-        length.encode(&mut self.buf[self.index..]);
-        self.index += sz;
-        Ok(())
+        let mut out = [0u8; 8];
+        length.encode(&mut out);
+        self.bytes(&out[..length.size_bytes()])
     }
 
-    fn prim_identifier(&mut self, tag: u32) -> Result<(), Error> {
+    // an IMPLICIT tag being set replace the class and tag
+    fn class_identifier(&mut self, class: Class, pc: PC, tag: u32) -> Result<(), Error> {
+        let (class, tag) = self.implicit.take().unwrap_or((class, tag));
         let ident = Identifier {
-            pc: PC::Primitive,
-            class: Class::Universal,
+            pc,
+            class,
             tag: TagEncoded::new_smallest(tag),
         };
         self.identifier(&ident)
     }
 
-    fn constructed_identifier(&mut self, tag: u32) -> Result<(), Error> {
-        let ident = Identifier {
-            pc: PC::Constructed,
-            class: Class::Universal,
-            tag: TagEncoded::new_smallest(tag),
-        };
-        self.identifier(&ident)
+    fn prim_identifier(&mut self, tag: u32) -> Result<(), Error> {
+        self.class_identifier(Class::Universal, PC::Primitive, tag)
+    }
+
+    /// write a primitive universal element with the given content
+    pub(crate) fn primitive(&mut self, tag: u32, data: &[u8]) -> Result<(), Error> {
+        self.prim_identifier(tag)?;
+        self.copy_data(data)
     }
 
     fn copy_data(&mut self, data: &[u8]) -> Result<(), Error> {
         self.length(Length::new_smallest(data.len()))?;
-        self.check_length(data.len())?;
-        let end_index = self.index + data.len();
-        self.buf[self.index..end_index].copy_from_slice(data);
-        self.index += data.len();
-        Ok(())
+        self.bytes(data)
     }
 
     /// Write a boolean to the DER writer
@@ -78,29 +258,139 @@ impl<'a> Writer<'a> {
     }
 
     /// Write an Integer to the DER writer
-    pub fn integer<'b>(&mut self, integer: &'b Integer) -> Result<(), Error> {
+    pub fn integer(&mut self, integer: &Integer) -> Result<(), Error> {
         self.prim_identifier(constants::TAG_INTEGER)?;
         self.copy_data(integer.as_ref())
     }
 
+    /// Write a signed primitive as an Integer to the DER writer
+    pub fn integer_i64(&mut self, v: i64) -> Result<(), Error> {
+        let mut buf = [0u8; 9];
+        let integer = Integer8Bit::encode_i64(v, &mut buf);
+        self.primitive(constants::TAG_INTEGER, integer.as_ref())
+    }
+
+    /// Write an unsigned primitive as an Integer to the DER writer
+    pub fn integer_u64(&mut self, v: u64) -> Result<(), Error> {
+        let mut buf = [0u8; 9];
+        let integer = Integer8Bit::encode_u64(v, &mut buf);
+        self.primitive(constants::TAG_INTEGER, integer.as_ref())
+    }
+
     /// Write an Enumerated to the DER writer
-    pub fn enumerated<'b>(&mut self, enumerated: &'b Enumerated) -> Result<(), Error> {
+    pub fn enumerated(&mut self, enumerated: &Enumerated) -> Result<(), Error> {
         self.prim_identifier(constants::TAG_ENUMERATED)?;
         self.copy_data(enumerated.as_ref())
     }
 
     /// Write a bitstring to the DER writer
-    pub fn bitstring<'b>(&mut self, obj: &'b BitString) -> Result<(), Error> {
+    pub fn bitstring(&mut self, obj: &BitString) -> Result<(), Error> {
         self.prim_identifier(constants::TAG_BIT_STRING)?;
         self.copy_data(obj.as_ref())
     }
 
     /// Write a octetstring to the DER writer
-    pub fn octetstring<'b>(&mut self, obj: &'b [u8]) -> Result<(), Error> {
+    pub fn octetstring(&mut self, obj: &[u8]) -> Result<(), Error> {
         self.prim_identifier(constants::TAG_OCTET_STRING)?;
         self.copy_data(obj.as_ref())
     }
 
+    /// Write an OID to the DER writer
+    pub fn oid(&mut self, oid: &OID) -> Result<(), Error> {
+        self.primitive(constants::TAG_OID, oid.as_ref())
+    }
+
+    /// Write a real to the DER writer
+    pub fn real(&mut self, real: &Real) -> Result<(), Error> {
+        self.primitive(constants::TAG_REAL, real.as_ref())
+    }
+
+    /// Write a f64 as a real to the DER writer
+    pub fn real_f64(&mut self, v: f64) -> Result<(), Error> {
+        let mut buf = [0u8; 10];
+        let real = Real::from_f64(v, &mut buf);
+        self.real(real)
+    }
+
+    /// Write a UTC time to the DER writer
+    pub fn utc_time(&mut self, time: &UtcTime) -> Result<(), Error> {
+        self.primitive(constants::TAG_UTC_TIME, time.as_ref())
+    }
+
+    /// Write a generalized time to the DER writer
+    pub fn generalized_time(&mut self, time: &GeneralizedTime) -> Result<(), Error> {
+        self.primitive(constants::TAG_GENERALIZED_TIME, time.as_ref())
+    }
+
+    writer_string!(
+        numeric_string,
+        implicit_numeric_string,
+        NumericString,
+        TAG_NUMERIC_STRING,
+        "numeric string"
+    );
+    writer_string!(
+        printable_string,
+        implicit_printable_string,
+        PrintableString,
+        TAG_PRINTABLE_STRING,
+        "printable string"
+    );
+    writer_string!(
+        t61_string,
+        implicit_t61_string,
+        T61String,
+        TAG_T61_STRING,
+        "T61 string"
+    );
+    writer_string!(
+        ia5_string,
+        implicit_ia5_string,
+        IA5String,
+        TAG_IA5_STRING,
+        "IA5 string"
+    );
+    writer_string!(
+        visible_string,
+        implicit_visible_string,
+        VisibleString,
+        TAG_VISIBLE_STRING,
+        "visible string"
+    );
+    writer_string!(
+        universal_string,
+        implicit_universal_string,
+        UniversalString,
+        TAG_UNIVERSAL_STRING,
+        "universal string"
+    );
+    writer_string!(
+        bmp_string,
+        implicit_bmp_string,
+        BMPString,
+        TAG_BMP_STRING,
+        "BMP string"
+    );
+
+    /// Write any string to the DER writer, with the tag of its character string type
+    pub fn any_string(&mut self, string: &AnyString<'_>) -> Result<(), Error> {
+        match string {
+            AnyString::Utf8(s) => self.utf8_string(s),
+            AnyString::Numeric(s) => self.numeric_string(s),
+            AnyString::Printable(s) => self.printable_string(s),
+            AnyString::T61(s) => self.t61_string(s),
+            AnyString::IA5(s) => self.ia5_string(s),
+            AnyString::Visible(s) => self.visible_string(s),
+            AnyString::Universal(s) => self.universal_string(s),
+            AnyString::BMP(s) => self.bmp_string(s),
+        }
+    }
+
+    /// Write a relative OID to the DER writer
+    pub fn relative_oid(&mut self, oid: &RelativeOID) -> Result<(), Error> {
+        self.primitive(constants::TAG_RELATIVE_OID, oid.as_ref())
+    }
+
     /// Write a null to the DER writer
     pub fn null(&mut self) -> Result<(), Error> {
         self.prim_identifier(constants::TAG_NULL)?;
@@ -108,38 +398,213 @@ impl<'a> Writer<'a> {
     }
 
     /// Write a utf8 string to the DER writer
-    pub fn utf8_string<'b>(&mut self, str: &'b str) -> Result<(), Error> {
+    pub fn utf8_string(&mut self, str: &str) -> Result<(), Error> {
         let bytes = str.as_bytes();
         self.prim_identifier(constants::TAG_UTF8_STRING)?;
         self.copy_data(bytes)
     }
 
-    /// Write a sequence to the DER writer
-    pub fn sequence<F>(&mut self, f: F) -> Result<(), Error>
+    /// Write an element verbatim from its full encoding
+    ///
+    /// When written with an IMPLICIT tag, the class and tag of the identifier are
+    /// replaced, and the element keeps its primitive or constructed encoding
+    pub fn raw_tlv(&mut self, tlv: &Tlv<'_>) -> Result<(), Error> {
+        if self.implicit.is_none() {
+            return self.bytes(tlv.as_bytes());
+        }
+        let ident = tlv.identifier();
+        self.class_identifier(ident.class, ident.pc, ident.tag.value())?;
+        self.copy_data(tlv.content())
+    }
+
+    /// Write a sequence to the DER writer, where the elements are written by f
+    ///
+    /// The value returned by f is returned once the sequence is complete.
+    pub fn sequence<F, R>(&mut self, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<R, Error>,
+    {
+        self.constructed(Class::Universal, constants::TAG_SEQUENCE, f)
+    }
+
+    /// Write a set to the DER writer
+    ///
+    /// The elements written by f are sorted in the canonical order of a SET,
+    /// by their class and then their tag number, so they need distinct tags.
+    pub fn set<F, R>(&mut self, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<R, Error>,
+    {
+        self.sorted_set(tag_order, f)
+    }
+
+    /// Write a set of every value to the DER writer
+    ///
+    /// The encoded values are sorted in the DER order, whatever the order of the values
+    pub fn set_of<I>(&mut self, values: I) -> Result<(), Error>
+    where
+        I: IntoIterator,
+        I::Item: DerEncode,
+    {
+        self.sorted_set(set_of_order, |w| {
+            for v in values {
+                v.encode(w)?;
+            }
+            Ok(())
+        })
+    }
+
+    // write a set where the elements written by f are sorted in the given order
+    fn sorted_set<F, R>(&mut self, order: fn(&[u8], &[u8]) -> Ordering, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<R, Error>,
+    {
+        self.constructed(Class::Universal, constants::TAG_SET, |w| {
+            let start = w.index;
+            let r = f(w)?;
+            // the order doesn't change the size
+            if !w.is_counting() {
+                let end = w.index;
+                sort_elements(&mut w.buf[start..end], order);
+            }
+            Ok(r)
+        })
+    }
+
+    /// Write an EXPLICIT tagged element to the DER writer,
+    /// where the inner element is written by f
+    pub fn explicit<F, R>(&mut self, class: Class, tag: u32, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<R, Error>,
+    {
+        self.constructed(class, tag, f)
+    }
+
+    // write an element with f, where its universal tag is replaced by an IMPLICIT tag
+    fn with_implicit<F, R>(&mut self, class: Class, tag: u32, f: F) -> Result<R, Error>
     where
-        F: Fn(&mut Self) -> Result<(), Error>,
+        F: FnOnce(&mut Self) -> Result<R, Error>,
     {
-        self.constructed_identifier(constants::TAG_SEQUENCE)?;
+        self.implicit = Some((class, tag));
+        let r = f(self);
+        self.implicit = None;
+        r
+    }
+
+    writer_implicit!(implicit_bool, bool, bool, "boolean");
+    writer_implicit!(implicit_integer, integer, &Integer, "integer");
+    writer_implicit!(implicit_enumerated, enumerated, &Enumerated, "enumerated");
+    writer_implicit!(implicit_bitstring, bitstring, &BitString, "bitstring");
+    writer_implicit!(implicit_octetstring, octetstring, &[u8], "octetstring");
+    writer_implicit!(implicit_utf8_string, utf8_string, &str, "utf8 string");
+    writer_implicit!(implicit_oid, oid, &OID, "OID");
+    writer_implicit!(implicit_real, real, &Real, "real");
+    writer_implicit!(implicit_utc_time, utc_time, &UtcTime, "UTC time");
+    writer_implicit!(
+        implicit_generalized_time,
+        generalized_time,
+        &GeneralizedTime,
+        "generalized time"
+    );
+    writer_implicit!(
+        implicit_relative_oid,
+        relative_oid,
+        &RelativeOID,
+        "relative OID"
+    );
+
+    /// Write a null with an IMPLICIT tag to the DER writer
+    pub fn implicit_null(&mut self, class: Class, tag: u32) -> Result<(), Error> {
+        self.with_implicit(class, tag, |w| w.null())
+    }
+
+    /// Write a sequence with an IMPLICIT tag to the DER writer
+    pub fn implicit_sequence<F, R>(&mut self, class: Class, tag: u32, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<R, Error>,
+    {
+        self.with_implicit(class, tag, |w| w.sequence(f))
+    }
+
+    fn constructed<F, R>(&mut self, class: Class, tag: u32, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<R, Error>,
+    {
+        self.class_identifier(class, PC::Constructed, tag)?;
+
+        // the length is already known, so it can be written before the content
+        #[cfg(feature = "alloc")]
+        if let Buffer::Replay(_, lengths) = &mut self.buf {
+            let length = lengths.pop().ok_or(Error::LengthMismatch)?;
+            self.length(Length::new_smallest(length))?;
+            let position_data = self.index;
+            let r = f(self)?;
+            if self.index - position_data != length {
+                return Err(Error::LengthMismatch);
+            }
+            return Ok(r);
+        }
+        #[cfg(feature = "alloc")]
+        let record_slot = match &mut self.buf {
+            Buffer::Record(lengths) => {
+                lengths.push(0);
+                Some(lengths.len() - 1)
+            }
+            _ => None,
+        };
+
         let position_length = self.index;
         self.length(Length::Short(0))?;
         let position_data = self.index;
-        f(self)?;
+        let r = f(self)?;
         let diff = self.index - position_data;
-        if diff < 0x80 {
-            // can reuse the same length bytes position
-            let actual_length = Length::Short(diff as u8);
-            actual_length.encode(&mut self.buf[position_length..]);
-        } else {
+
+        #[cfg(feature = "alloc")]
+        if let (Some(slot), Buffer::Record(lengths)) = (record_slot, &mut self.buf) {
+            lengths[slot] = diff;
+        }
+
+        let actual_length = Length::new_smallest(diff);
+        if diff >= 0x80 {
             // need to move data by couple of bytes to be able
             // to write the new length
-            let actual_length = Length::new_smallest(diff);
             let move_forward = actual_length.size_bytes() - 1;
-            self.buf[position_data..].copy_within(0..diff, move_forward);
+            self.check_length(move_forward)?;
+            if !self.is_counting() {
+                self.buf[position_data..].copy_within(0..diff, move_forward);
+            }
             self.index += move_forward;
+        }
+        if !self.is_counting() {
             actual_length.encode(&mut self.buf[position_length..]);
         }
 
-        Ok(())
+        Ok(r)
+    }
+
+    /// Write any encodable value to the DER writer
+    pub fn encode<T: DerEncode + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.encode(self)
+    }
+
+    /// Write any encodable value to the DER writer, wrapped in a context specific
+    /// EXPLICIT tag
+    pub fn encode_explicit<T: DerEncode + ?Sized>(
+        &mut self,
+        tag: u32,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.explicit(Class::Context, tag, |w| value.encode(w))
+    }
+
+    /// Write any encodable value to the DER writer, where its tag
+    /// is replaced by a context specific IMPLICIT tag
+    pub fn encode_implicit<T: DerEncode + ?Sized>(
+        &mut self,
+        tag: u32,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.with_implicit(Class::Context, tag, |w| value.encode(w))
     }
 
     /// Get the current position in the Writer, which is also the number of byte written
@@ -153,6 +618,126 @@ impl<'a> Writer<'a> {
     }
 }
 
+/// ASN.1 DER Writer to a vector growing as needed
+///
+/// All the methods of the Writer are available through Deref,
+/// and the encoding is returned by `finish`.
+#[cfg(feature = "alloc")]
+pub struct VecWriter(Writer<'static>);
+
+#[cfg(feature = "alloc")]
+impl VecWriter {
+    /// create a new DER writer to an empty vector
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// create a new DER writer to a vector with an initial capacity
+    pub fn with_capacity(capacity: usize) -> Self {
+        VecWriter(Writer::with_buffer(Buffer::Vec(Vec::with_capacity(
+            capacity,
+        ))))
+    }
+
+    /// Return the vector with the valid DER stream of data
+    pub fn finish(self) -> Vec<u8> {
+        let index = self.0.index;
+        match self.0.buf {
+            Buffer::Vec(mut v) => {
+                v.truncate(index);
+                v
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Default for VecWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Deref for VecWriter {
+    type Target = Writer<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl DerefMut for VecWriter {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// ASN.1 DER Writer that only count the size of the encoding
+///
+/// All the methods of the Writer are available through Deref, and
+/// the size of everything written is returned by `finish`.
+pub struct SizeCounter(Writer<'static>);
+
+impl SizeCounter {
+    /// create a new DER size counter
+    pub fn new() -> Self {
+        SizeCounter(Writer::with_buffer(Buffer::Count))
+    }
+
+    /// Return the size of the DER encoding of everything written
+    pub fn finish(self) -> usize {
+        self.0.index
+    }
+}
+
+impl Default for SizeCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for SizeCounter {
+    type Target = Writer<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for SizeCounter {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// Encode a value to a vector allocated once with the exact size of the encoding
+///
+/// The value is encoded twice: first to count the size of the encoding and the
+/// length of every constructed element, then to write the encoding from front
+/// to back, without moving the data of the long constructed elements.
+#[cfg(feature = "alloc")]
+pub fn encode_to_vec<T: DerEncode + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    let mut counter = Writer::with_buffer(Buffer::Record(Vec::new()));
+    value.encode(&mut counter)?;
+    let size = counter.index;
+    let mut lengths = match counter.buf {
+        Buffer::Record(lengths) => lengths,
+        _ => unreachable!(),
+    };
+    lengths.reverse();
+
+    let mut writer = Writer::with_buffer(Buffer::Replay(alloc::vec![0; size], lengths));
+    value.encode(&mut writer)?;
+    match writer.buf {
+        // every recorded length is replayed, and the size is the counted one
+        Buffer::Replay(v, lengths) if writer.index == size && lengths.is_empty() => Ok(v),
+        _ => Err(Error::LengthMismatch),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -176,4 +761,238 @@ mod tests {
         assert_eq!(slice.len(), 86, "length doesn't match");
         assert_eq!(total, 751, "byte sum doesn't match");
     }
+
+    #[test]
+    pub fn encode_context_tags() {
+        let mut buf = [0u8; 64];
+        let mut writer = Writer::new(&mut buf);
+        let two = Integer::from_slice(&[2]).unwrap();
+        let five = Integer::from_slice(&[5]).unwrap();
+        writer
+            .sequence(|w| {
+                w.explicit(Class::Context, 0, |w| w.integer(two))?;
+                w.implicit_integer(Class::Context, 2, five)?;
+                w.implicit_sequence(Class::Context, 3, |w| w.bool(true))?;
+                w.implicit_null(Class::Application, 40)
+            })
+            .expect("sequence");
+        assert_eq!(
+            writer.finish(),
+            &b"\x30\x10\xa0\x03\x02\x01\x02\x82\x01\x05\xa3\x03\x01\x01\xff\x5f\x28\x00"[..]
+        );
+    }
+
+    #[test]
+    pub fn encode_primitive_integers() {
+        let mut buf = [0u8; 64];
+        let mut writer = Writer::new(&mut buf);
+        writer.integer_u64(0).unwrap();
+        writer.integer_u64(128).unwrap();
+        writer.integer_i64(-128).unwrap();
+        writer.integer_i64(-129).unwrap();
+        writer.integer_u64(u64::MAX).unwrap();
+        assert_eq!(
+            writer.finish(),
+            &b"\x02\x01\x00\x02\x02\x00\x80\x02\x01\x80\x02\x02\xff\x7f\x02\x09\x00\xff\xff\xff\xff\xff\xff\xff\xff"[..]
+        );
+    }
+
+    #[test]
+    pub fn encode_set_of() {
+        let mut buf = [0u8; 64];
+        let mut writer = Writer::new(&mut buf);
+        let strings: [&[u8]; 4] = [b"\x02\x01", b"\x01", b"", b"\x02"];
+        writer.set_of(strings.iter()).expect("set of");
+        assert_eq!(
+            writer.finish(),
+            &b"\x31\x0c\x04\x00\x04\x01\x01\x04\x01\x02\x04\x02\x02\x01"[..]
+        );
+
+        let mut buf = [0u8; 64];
+        let mut writer = Writer::new(&mut buf);
+        let oid = OID::parse_from_slice(&[0x55, 0x04, 0x03]).unwrap();
+        writer
+            .set(|w| {
+                w.implicit_bool(Class::Context, 1, true)?;
+                w.null()?;
+                w.oid(oid)
+            })
+            .expect("set");
+        assert_eq!(
+            writer.finish(),
+            &b"\x31\x0a\x05\x00\x06\x03\x55\x04\x03\x81\x01\xff"[..]
+        );
+        // the order is by tag number, whether the element is constructed or not,
+        // while the encodings would put the primitive [1] before the constructed [0]
+        let mut buf = [0u8; 64];
+        let mut writer = Writer::new(&mut buf);
+        writer
+            .set(|w| {
+                w.implicit_bool(Class::Context, 1, true)?;
+                w.explicit(Class::Context, 0, |w| w.null())
+            })
+            .expect("set");
+        assert_eq!(
+            writer.finish(),
+            &b"\x31\x07\xa0\x02\x05\x00\x81\x01\xff"[..]
+        );
+    }
+
+    #[test]
+    pub fn encode_raw_tlv() {
+        let data = b"\x30\x06\x02\x01\x01\x01\x01\xff";
+        let tlv = crate::der::Reader::new(&data[..]).any_raw().expect("tlv");
+        let mut buf = [0u8; 64];
+        let mut writer = Writer::new(&mut buf);
+        writer.sequence(|w| w.raw_tlv(&tlv)).expect("sequence");
+        assert_eq!(
+            writer.finish(),
+            &b"\x30\x08\x30\x06\x02\x01\x01\x01\x01\xff"[..]
+        );
+
+        let mut buf = [0u8; 4];
+        let mut writer = Writer::new(&mut buf);
+        assert!(writer.raw_tlv(&tlv).is_err());
+
+        // an IMPLICIT tag replaces the identifier, keeping the constructed bit
+        let integer_tlv = crate::der::Reader::new(&data[2..5]).any_raw().expect("tlv");
+        let mut buf = [0u8; 64];
+        let mut writer = Writer::new(&mut buf);
+        writer.encode_implicit(1, &tlv).expect("implicit sequence");
+        writer
+            .encode_implicit(2, &integer_tlv)
+            .expect("implicit integer");
+        assert_eq!(
+            writer.finish(),
+            &b"\xa1\x06\x02\x01\x01\x01\x01\xff\x82\x01\x01"[..]
+        );
+    }
+
+    #[test]
+    pub fn encode_long_sequence_buffer_too_small() {
+        // the content fits, but not the long form of the sequence length
+        let mut buf = [0u8; 132];
+        let mut writer = Writer::new(&mut buf);
+        let r = writer.sequence(|w| w.octetstring(&[0u8; 128]));
+        assert!(matches!(r, Err(Error::BufferTooSmall(132))));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    pub fn encode_vec_writer() {
+        let ostring = [2u8; 300];
+        let mut writer = VecWriter::new();
+        writer
+            .sequence(|w| {
+                w.sequence(|w| w.octetstring(&ostring))?;
+                w.integer_u64(0x1234)
+            })
+            .expect("sequence");
+        assert_eq!(writer.current_position(), 316);
+        let v = writer.finish();
+        assert_eq!(v.len(), 316);
+        assert_eq!(
+            &v[..12],
+            &b"\x30\x82\x01\x38\x30\x82\x01\x30\x04\x82\x01\x2c"[..]
+        );
+        assert_eq!(&v[312..], &b"\x02\x02\x12\x34"[..]);
+
+        let mut writer = VecWriter::with_capacity(16);
+        writer.encode(&true).expect("bool");
+        assert_eq!(writer.finish(), b"\x01\x01\xff");
+    }
+
+    #[test]
+    pub fn encode_size_counter() {
+        let ostring = [2u8; 200];
+        let value = (true, &ostring[..], (0x1234u32, &[1u16, 2, 3][..]));
+        let mut buf = [0u8; 512];
+        let mut writer = Writer::new(&mut buf);
+        writer.encode(&value).expect("encode");
+        let expected = writer.finish();
+        assert_eq!(value.encoded_len().expect("size"), expected.len());
+
+        let mut counter = SizeCounter::new();
+        counter
+            .set(|w| {
+                w.octetstring(&ostring)?;
+                w.implicit_bool(Class::Context, 0, false)
+            })
+            .expect("set");
+        assert_eq!(counter.finish(), 3 + 3 + 200 + 3);
+
+        #[cfg(feature = "alloc")]
+        {
+            let v = encode_to_vec(&value).expect("encode");
+            assert_eq!(v.capacity(), expected.len());
+            assert_eq!(&v[..], expected);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    pub fn encode_to_vec_length_mismatch() {
+        use core::cell::Cell;
+
+        // a value which is not encoded the same way every time, as a sequence
+        // of more and more NULL elements, each in a sequence if nested
+        struct Changing(Cell<usize>, bool);
+        impl DerEncode for Changing {
+            fn encode(&self, writer: &mut Writer<'_>) -> Result<(), Error> {
+                let n = self.0.get();
+                self.0.set(n + 1);
+                writer.sequence(|w| {
+                    for _ in 0..n {
+                        if self.1 {
+                            w.sequence(|w| w.null())?;
+                        } else {
+                            w.null()?;
+                        }
+                    }
+                    Ok(())
+                })
+            }
+        }
+
+        // the content doesn't match the recorded length, or there's no more
+        // recorded length for the nested elements
+        for nested in [false, true].iter() {
+            match encode_to_vec(&Changing(Cell::new(1), *nested)) {
+                Err(Error::LengthMismatch) => {}
+                r => panic!("unexpected {:?}", r),
+            }
+        }
+    }
+
+    #[test]
+    pub fn encode_closure_state() {
+        let mut buf = [0u8; 64];
+        let mut writer = Writer::new(&mut buf);
+        let values = [1u64, 2, 300].iter().copied();
+        let mut written = 0;
+        let count = writer
+            .sequence(|w| {
+                let mut count = 0;
+                for v in values {
+                    w.integer_u64(v)?;
+                    count += 1;
+                }
+                written = w.current_position();
+                Ok(count)
+            })
+            .expect("sequence");
+        assert_eq!(count, 3);
+        assert_eq!(written, 12);
+        assert_eq!(
+            writer.finish(),
+            &b"\x30\x0a\x02\x01\x01\x02\x01\x02\x02\x02\x01\x2c"[..]
+        );
+    }
+
+    #[test]
+    pub fn set_of_order_padding() {
+        assert_eq!(set_of_order(b"\x01", b"\x01\x00"), Ordering::Equal);
+        assert_eq!(set_of_order(b"\x01", b"\x01\x01"), Ordering::Less);
+        assert_eq!(set_of_order(b"\x02", b"\x01\x01"), Ordering::Greater);
+    }
 }
@@ -33,7 +33,8 @@ impl TagEncoded {
     }
 
     pub fn new_smallest(v: u32) -> Self {
-        if v < 0x80 {
+        // the short form use only 5 bits, and 0x1f is the long form marker
+        if v < 0x1f {
             TagEncoded::Short(v as u8)
         } else {
             TagEncoded::Long(v)
@@ -106,9 +107,6 @@ pub enum DecodeError {
     TagEncodingNonCanonical,
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct OutputBufferTooSmall;
-
 impl Identifier {
     pub fn decode(slice: &[u8]) -> Result<(Self, usize), DecodeError> {
         if slice.is_empty() {
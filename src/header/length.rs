@@ -20,6 +20,13 @@ pub enum LengthDecodeError {
     EncodingIncomplete,
     /// Length encoded is bigger than the reasonable limit
     EncodingOverflow,
+    /// Initial byte 0xff is reserved for future extension
+    EncodingReserved,
+    /// Long form used for a value that fit in the short form (DER only)
+    NonMinimalLongForm,
+    /// Long form value starting with a zero byte, i.e. using
+    /// more bytes than necessary (DER only)
+    NonMinimalLeadingZero,
 }
 
 impl Length {
@@ -57,14 +64,24 @@ impl Length {
         put_length(self, out)
     }
 
+    /// Decode a length using the relaxed BER rules, where the long form
+    /// can be used with any number of bytes
     pub fn decode(buf: &[u8]) -> Result<(Self, usize), LengthDecodeError> {
-        get_length(buf)
+        get_length(buf, false)
+    }
+
+    /// Decode a length using the DER rules, where only the minimal
+    /// encoding of a given value is accepted
+    pub fn decode_der(buf: &[u8]) -> Result<(Self, usize), LengthDecodeError> {
+        get_length(buf, true)
     }
 }
 
 // length encoding is either 0x80 for indefinite, anything less is a short encoding,
 // and anything above give the number of byte
-fn get_length(slice: &[u8]) -> Result<(Length, usize), LengthDecodeError> {
+//
+// when minimal is set, the long form need to be the smallest possible for the value
+fn get_length(slice: &[u8], minimal: bool) -> Result<(Length, usize), LengthDecodeError> {
     if slice.is_empty() {
         return Err(LengthDecodeError::EncodingIncomplete);
     }
@@ -73,6 +90,8 @@ fn get_length(slice: &[u8]) -> Result<(Length, usize), LengthDecodeError> {
 
     if f == 0b1000_0000 {
         Ok((Length::Indefinite, 1))
+    } else if f == 0xff {
+        Err(LengthDecodeError::EncodingReserved)
     } else if (f & 0b1000_0000) != 0 {
         let nb_bytes = f & 0b0111_1111;
         let mut acc = 0u32;
@@ -82,15 +101,19 @@ fn get_length(slice: &[u8]) -> Result<(Length, usize), LengthDecodeError> {
             return Err(LengthDecodeError::EncodingIncomplete);
         }
 
-        let mut index = 1;
-        for _ in 0..nb_bytes {
-            let b: u8 = slice[index];
-            index += 1;
-            acc = acc
-                .checked_shl(8)
-                .ok_or(LengthDecodeError::EncodingOverflow)?
-                .checked_add(u32::from(b))
-                .ok_or(LengthDecodeError::EncodingOverflow)?;
+        if minimal && slice[1] == 0 {
+            return Err(LengthDecodeError::NonMinimalLeadingZero);
+        }
+
+        for b in &slice[1..total_size] {
+            // checked_shl only check the shift amount, not the bits shifted out
+            if acc > (u32::MAX >> 8) {
+                return Err(LengthDecodeError::EncodingOverflow);
+            }
+            acc = (acc << 8) | u32::from(*b);
+        }
+        if minimal && acc < 0x80 {
+            return Err(LengthDecodeError::NonMinimalLongForm);
         }
         let len = Length::Long {
             nb_bytes,
@@ -189,4 +212,44 @@ mod tests {
             assert_eq!(new_length, length)
         }
     }
+
+    #[test]
+    fn decode_der_non_minimal() {
+        // long form for a short value
+        let buf = [0x81, 0x7f];
+        assert!(Length::decode(&buf).is_ok());
+        match Length::decode_der(&buf) {
+            Err(LengthDecodeError::NonMinimalLongForm) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+
+        // leading zero
+        let buf = [0x82, 0x00, 0x80];
+        assert!(Length::decode(&buf).is_ok());
+        match Length::decode_der(&buf) {
+            Err(LengthDecodeError::NonMinimalLeadingZero) => {}
+            r => panic!("unexpected {:?}", r),
+        }
+
+        // too many bytes
+        let buf = [0x86, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00];
+        assert_eq!(
+            Length::decode(&buf).unwrap(),
+            (
+                Length::Long {
+                    nb_bytes: 6,
+                    value: 0x100
+                },
+                7
+            )
+        );
+        assert!(Length::decode_der(&buf).is_err());
+
+        // reserved
+        assert!(Length::decode(&[0xff]).is_err());
+
+        // minimal encodings
+        assert!(Length::decode_der(&[0x81, 0x80]).is_ok());
+        assert!(Length::decode_der(&[0x82, 0x01, 0x00]).is_ok());
+    }
 }
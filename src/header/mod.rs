@@ -2,5 +2,5 @@ pub mod constants;
 mod identifier;
 mod length;
 
-pub use identifier::{Class, Identifier, TagEncoded, PC};
-pub use length::Length;
+pub use identifier::{Class, DecodeError as IdentifierDecodeError, Identifier, TagEncoded, PC};
+pub use length::{Length, LengthDecodeError};
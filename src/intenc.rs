@@ -21,9 +21,11 @@ macro_rules! to_primitive7 {
             // for each byte limb.
             let mut acc = (self.0[0] & 0b0111_1111) as $type;
             for c in &self.0[1..] {
-                acc = acc
-                    .checked_shl(7)?
-                    .checked_add((c & 0b0111_1111) as $type)?
+                // checked_shl doesn't detect the bits shifted out
+                if acc >> (<$type>::BITS - 7) != 0 {
+                    return None;
+                }
+                acc = (acc << 7) | (c & 0b0111_1111) as $type;
             }
             Some(acc)
         }
@@ -82,23 +84,62 @@ pub(crate) struct Integer8Bit([u8]);
 
 macro_rules! to_primitive8 {
     ($type: ident, $name: ident) => {
-        /// Try to convert to the primitive
+        /// Try to convert to the unsigned primitive
         ///
-        /// If there's an overflown then nothing is returned
+        /// If the integer is negative or overflow the primitive then nothing is returned
         pub fn $name(&self) -> Option<$type> {
-            // this function assume that the data has been checked properly
-            // so that the first byte is not a long zero,
-            // and that the continuation bit are correctly set
-            // for each byte limb.
-            let mut acc = self.0[0] as $type;
-            for c in &self.0[1..] {
-                acc = acc.checked_shl(8)?.checked_add(*c as $type)?
+            if self.is_negative() {
+                return None;
+            }
+            let mut bytes = &self.0[..];
+            while !bytes.is_empty() && bytes[0] == 0 {
+                bytes = &bytes[1..];
+            }
+            if bytes.len() > core::mem::size_of::<$type>() {
+                return None;
+            }
+            let mut acc: $type = 0;
+            for c in bytes {
+                // shifting all the bits out is only possible when acc is still 0
+                acc = acc.checked_shl(8).unwrap_or(0) | (*c as $type);
             }
             Some(acc)
         }
     };
 }
 
+macro_rules! to_signed_primitive8 {
+    ($type: ident, $utype: ident, $name: ident) => {
+        /// Try to convert to the signed primitive
+        ///
+        /// If the integer overflow the primitive then nothing is returned
+        pub fn $name(&self) -> Option<$type> {
+            // this function assume that the data has been checked properly
+            // so that there's no redundant leading bytes
+            if self.0.len() > core::mem::size_of::<$type>() {
+                return None;
+            }
+            let mut acc: $utype = if self.is_negative() { !0 } else { 0 };
+            for c in &self.0 {
+                acc = acc.checked_shl(8).unwrap_or(0) | (*c as $utype);
+            }
+            Some(acc as $type)
+        }
+    };
+}
+
+/// Remove the redundant leading bytes of a two's complement big endian integer,
+/// which are 0x00 followed by a positive byte, or 0xff followed by a negative byte
+pub(crate) fn strip_redundant_sign_bytes(mut slice: &[u8]) -> &[u8] {
+    while slice.len() > 1
+        && ((slice[0] == 0x00 && slice[1] & 0x80 == 0)
+            || (slice[0] == 0xff && slice[1] & 0x80 != 0))
+    {
+        slice = &slice[1..];
+    }
+    slice
+}
+
 impl Integer8Bit {
     /// transform a raw slice into a Integer8Bit slice,
     /// no verification is done by this call
@@ -107,19 +148,48 @@ impl Integer8Bit {
     }
 
     /// Try to parse from a slice
+    ///
+    /// The slice need to be the minimal two's complement encoding of the integer,
+    /// so it cannot start with a redundant 0x00 or 0xff byte
     pub fn from_slice(slice: &[u8]) -> Result<&Self, ()> {
-        if slice.is_empty() || slice[0] == 0 {
+        if slice.is_empty() || strip_redundant_sign_bytes(slice).len() != slice.len() {
             return Err(());
         }
         Ok(Self::unverified_from_slice(slice))
     }
 
+    /// Encode a signed value in its minimal two's complement form in the out buffer
+    pub(crate) fn encode_i64(v: i64, out: &mut [u8; 9]) -> &Self {
+        let sign = if v < 0 { 0xff } else { 0x00 };
+        out[0] = sign;
+        out[1..].copy_from_slice(&v.to_be_bytes());
+        Self::unverified_from_slice(strip_redundant_sign_bytes(&out[..]))
+    }
+
+    /// Encode an unsigned value in its minimal two's complement form in the out buffer
+    pub(crate) fn encode_u64(v: u64, out: &mut [u8; 9]) -> &Self {
+        out[0] = 0;
+        out[1..].copy_from_slice(&v.to_be_bytes());
+        Self::unverified_from_slice(strip_redundant_sign_bytes(&out[..]))
+    }
+
+    /// Return if the integer is strictly negative
+    pub fn is_negative(&self) -> bool {
+        (self.0[0] & 0x80) != 0
+    }
+
     to_primitive8!(u128, to_u128);
     to_primitive8!(u64, to_u64);
     to_primitive8!(u32, to_u32);
     to_primitive8!(u16, to_u16);
     to_primitive8!(u8, to_u8);
 
+    to_signed_primitive8!(i128, u128, to_i128);
+    to_signed_primitive8!(i64, u64, to_i64);
+    to_signed_primitive8!(i32, u32, to_i32);
+    to_signed_primitive8!(i16, u16, to_i16);
+    to_signed_primitive8!(i8, u8, to_i8);
+
     /*
     pub fn as_be() -> BeIntegerBytes<'a> {}
 
@@ -130,3 +200,83 @@ impl Integer8Bit {
 }
 
 slice_reexport_asref!(Integer8Bit);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer8bit_canonical() {
+        assert!(Integer8Bit::from_slice(&[]).is_err());
+        assert!(Integer8Bit::from_slice(&[0x00]).is_ok());
+        assert!(Integer8Bit::from_slice(&[0xff]).is_ok());
+        assert!(Integer8Bit::from_slice(&[0x00, 0x80]).is_ok());
+        assert!(Integer8Bit::from_slice(&[0xff, 0x7f]).is_ok());
+        assert!(Integer8Bit::from_slice(&[0x00, 0x7f]).is_err());
+        assert!(Integer8Bit::from_slice(&[0xff, 0x80]).is_err());
+        assert!(Integer8Bit::from_slice(&[0x00, 0x00]).is_err());
+    }
+
+    #[test]
+    fn integer8bit_conversion() {
+        let zero = Integer8Bit::from_slice(&[0x00]).unwrap();
+        assert_eq!(zero.to_u8(), Some(0));
+        assert_eq!(zero.to_i64(), Some(0));
+        assert!(!zero.is_negative());
+
+        let v = Integer8Bit::from_slice(&[0x00, 0x80]).unwrap();
+        assert_eq!(v.to_u8(), Some(0x80));
+        assert_eq!(v.to_i8(), None);
+        assert_eq!(v.to_i16(), Some(0x80));
+
+        let v = Integer8Bit::from_slice(&[0x80]).unwrap();
+        assert!(v.is_negative());
+        assert_eq!(v.to_u8(), None);
+        assert_eq!(v.to_i8(), Some(-128));
+        assert_eq!(v.to_i128(), Some(-128));
+
+        let v = Integer8Bit::from_slice(&[0xff, 0x7f]).unwrap();
+        assert_eq!(v.to_i8(), None);
+        assert_eq!(v.to_i16(), Some(-129));
+
+        let v = Integer8Bit::from_slice(&[0x01, 0x00, 0x00]).unwrap();
+        assert_eq!(v.to_u16(), None);
+        assert_eq!(v.to_u32(), Some(0x10000));
+
+        let max = Integer8Bit::from_slice(&[0x00, 0xff, 0xff, 0xff, 0xff]).unwrap();
+        assert_eq!(max.to_u32(), Some(u32::MAX));
+        assert_eq!(max.to_i32(), None);
+    }
+
+    #[test]
+    fn integer8bit_encode() {
+        let mut buf = [0u8; 9];
+        for v in &[
+            0i64,
+            1,
+            -1,
+            127,
+            128,
+            -128,
+            -129,
+            255,
+            256,
+            i64::MIN,
+            i64::MAX,
+        ] {
+            let i = Integer8Bit::encode_i64(*v, &mut buf);
+            assert!(Integer8Bit::from_slice(i.as_ref()).is_ok(), "{}", v);
+            assert_eq!(i.to_i64(), Some(*v));
+        }
+        for v in &[0u64, 1, 127, 128, 255, 256, u64::MAX] {
+            let i = Integer8Bit::encode_u64(*v, &mut buf);
+            assert!(Integer8Bit::from_slice(i.as_ref()).is_ok(), "{}", v);
+            assert_eq!(i.to_u64(), Some(*v));
+        }
+        assert_eq!(
+            Integer8Bit::encode_u64(128, &mut buf).as_ref(),
+            &[0x00, 0x80]
+        );
+        assert_eq!(Integer8Bit::encode_i64(-128, &mut buf).as_ref(), &[0x80]);
+    }
+}
@@ -1,4 +1,4 @@
-//! ASN.1 binary encoder and decoder (DER, possibly BER/CER in future)
+//! ASN.1 binary encoder and decoder (DER, BER reading and CER writing)
 //!
 //! The general principle of this crate is to avoid transforming information
 //! or re-allocating information. this should be compatible with no_std.
@@ -12,15 +12,17 @@
 
 #![no_std]
 
-#[cfg(not(feature = "std"))]
+#[cfg(any(feature = "alloc", test))]
 extern crate alloc;
 
-#[cfg(test)]
-#[macro_use]
+#[cfg(any(feature = "std", test))]
+#[cfg_attr(test, macro_use)]
 extern crate std;
 
 mod header;
 
+pub mod ber;
+pub mod cer;
 pub mod der;
 
 #[macro_use]
@@ -28,4 +30,8 @@ mod coretm;
 mod intenc;
 mod objects;
 
+#[cfg(feature = "oids")]
+pub mod oids;
+
+pub use header::{Class, Identifier, Length, TagEncoded, PC};
 pub use objects::*;
@@ -1,8 +1,9 @@
 //use crate::coretm::slice_reexport_asref;
-use crate::intenc::{Integer8Bit, IntegerContBit7};
+use crate::intenc::{strip_redundant_sign_bytes, Integer8Bit, IntegerContBit7};
 
 #[cfg(feature = "owned")]
 use alloc::vec::Vec;
+use core::fmt;
 
 macro_rules! typed_vec_and_slice {
     ($name: ident, $slice: ident) => {
@@ -51,18 +52,56 @@ macro_rules! type_slice_integer_method {
     };
 }
 
+macro_rules! type_slice_signed_integer_method {
+    ($name: ident) => {
+        impl $name {
+            /// Return if the value is strictly negative
+            pub fn is_negative(&self) -> bool {
+                self.0.is_negative()
+            }
+            pub fn to_i128(&self) -> Option<i128> {
+                self.0.to_i128()
+            }
+            pub fn to_i64(&self) -> Option<i64> {
+                self.0.to_i64()
+            }
+            pub fn to_i32(&self) -> Option<i32> {
+                self.0.to_i32()
+            }
+            pub fn to_i16(&self) -> Option<i16> {
+                self.0.to_i16()
+            }
+            pub fn to_i8(&self) -> Option<i8> {
+                self.0.to_i8()
+            }
+        }
+    };
+}
+
 typed_vec_and_slice!(BitStringOwned, BitString);
-//typed_vec_and_slice!(IA5StringOwned, IA5String);
+typed_vec_and_slice!(NumericStringOwned, NumericString);
+typed_vec_and_slice!(PrintableStringOwned, PrintableString);
+typed_vec_and_slice!(T61StringOwned, T61String);
+typed_vec_and_slice!(IA5StringOwned, IA5String);
+typed_vec_and_slice!(VisibleStringOwned, VisibleString);
+typed_vec_and_slice!(UniversalStringOwned, UniversalString);
+typed_vec_and_slice!(BMPStringOwned, BMPString);
 typed_vec_and_slice!(OIDOwned, OID);
+typed_vec_and_slice!(RelativeOIDOwned, RelativeOID);
+typed_vec_and_slice!(RealOwned, Real);
+typed_vec_and_slice!(UtcTimeOwned, UtcTime);
+typed_vec_and_slice!(GeneralizedTimeOwned, GeneralizedTime);
 
 type_reslice!(OIDComponent, IntegerContBit7);
 type_slice_integer_method!(OIDComponent);
 
 type_reslice!(Integer, Integer8Bit);
 type_slice_integer_method!(Integer);
+type_slice_signed_integer_method!(Integer);
 
 type_reslice!(Enumerated, Integer8Bit);
 type_slice_integer_method!(Enumerated);
+type_slice_signed_integer_method!(Enumerated);
 
 impl BitString {
     /// Return the total number of bits of the bitstring
@@ -83,62 +122,420 @@ impl BitString {
     }
 }
 
+/// Iterator over the subidentifiers of an OID or a RELATIVE-OID
 #[derive(Debug, Clone)]
 pub struct OIDComponents<'a> {
-    slice: &'a OID,
+    slice: &'a [u8],
     index: usize,
 }
 
+/// Iterator over all the arcs of an OID, including the first two
+#[derive(Debug, Clone)]
+pub struct OIDArcs<'a> {
+    oid: &'a OID,
+    components: OIDComponents<'a>,
+    second: Option<u128>,
+}
+
+// check that the slice is a non empty list of valid subidentifiers,
+// which all fit in the biggest primitive
+// const, so that the static encodings of the `oid!` macro are checked at compile time
+const fn check_subidentifiers(slice: &[u8]) -> Result<(), ()> {
+    if slice.is_empty() {
+        return Err(());
+    }
+    let mut index = 0;
+    while index < slice.len() {
+        // a subidentifier doesn't start with a zero limb
+        if slice[index] == 0b1000_0000 {
+            return Err(());
+        }
+        let mut acc: u128 = 0;
+        loop {
+            // the last limb is missing
+            if index == slice.len() {
+                return Err(());
+            }
+            // the subidentifier doesn't fit in a u128
+            if acc >> (u128::BITS - 7) != 0 {
+                return Err(());
+            }
+            acc = (acc << 7) | (slice[index] & 0b0111_1111) as u128;
+            index += 1;
+            if slice[index - 1] & 0b1000_0000 == 0 {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
 impl OID {
+    // the first subidentifier, which combine the first 2 arcs, and its size in bytes
+    fn first_subidentifier(&self) -> (u128, usize) {
+        match IntegerContBit7::parse_from_slice(&self.0) {
+            Err(_) => unreachable!(),
+            Ok((r, adv)) => (r.to_u128().unwrap(), adv),
+        }
+    }
+
+    // split the first subidentifier into the first 2 arcs
+    fn first_arcs(&self) -> (u8, u128) {
+        let (v, _) = self.first_subidentifier();
+        match v {
+            0..=39 => (0, v),
+            40..=79 => (1, v - 40),
+            _ => (2, v - 80),
+        }
+    }
+
+    /// Return the first arc, which is either 0, 1 or 2
     pub fn value1(&self) -> u8 {
-        self.0[0] / 40
+        self.first_arcs().0
     }
 
-    pub fn value2(&self) -> u8 {
-        self.0[0] % 40
+    /// Return the second arc, which is unbounded when the first arc is 2
+    pub fn value2(&self) -> u128 {
+        self.first_arcs().1
     }
 
     /// Return all trailing components, except the first and second value
     pub fn components(&self) -> OIDComponents<'_> {
+        let (_, index) = self.first_subidentifier();
         OIDComponents {
-            slice: self,
-            index: 1,
+            slice: &self.0,
+            index,
         }
     }
 
-    pub fn parse_from_slice(slice: &[u8]) -> Result<&Self, ()> {
-        if slice.is_empty() {
-            return Err(());
+    /// Return all the arcs, including the first and second value
+    pub fn arcs(&self) -> OIDArcs<'_> {
+        OIDArcs {
+            oid: self,
+            components: OIDComponents {
+                slice: &self.0,
+                index: 0,
+            },
+            second: None,
         }
-        let f = slice[0];
-        // only 0, 1, and 2 are allowed.
-        if (f / 40) > 2 {
-            return Err(());
+    }
+
+    /// Create an OID from a static encoding, used by the `oid!` macro
+    ///
+    /// The encoding is checked, which fails the compilation when evaluated in a const
+    #[doc(hidden)]
+    pub const fn __from_static(slice: &'static [u8]) -> &'static Self {
+        assert!(check_subidentifiers(slice).is_ok(), "invalid OID encoding");
+        unsafe { &*(slice as *const [u8] as *const OID) }
+    }
+
+    #[allow(clippy::result_unit_err)]
+    pub fn parse_from_slice(slice: &[u8]) -> Result<&Self, ()> {
+        // every subidentifier, including the first one which combine the first 2 arcs,
+        // need to be valid
+        check_subidentifiers(slice)?;
+        Ok(Self::from_raw_slice(slice))
+    }
+}
+
+impl fmt::Display for OID {
+    /// Display the OID in the dotted decimal notation (e.g. `1.2.840.10045.2.1`)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, arc) in self.arcs().enumerate() {
+            if i > 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{}", arc)?;
         }
-        let mut index = 1;
-        while index < slice.len() {
-            match IntegerContBit7::parse_from_slice(&slice[index..]) {
-                Err(_) => return Err(()),
-                Ok((_, adv)) => index += adv,
+        Ok(())
+    }
+}
+
+/// Error when building an OID from its arcs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OIDParseError {
+    /// An arc is empty in the dotted notation
+    ArcEmpty,
+    /// An arc contains something else than decimal digits
+    ArcInvalidCharacter(u8),
+    /// An arc is too big to be represented
+    ArcOverflow,
+    /// An OID need at least 2 arcs, and a RELATIVE-OID at least 1 arc
+    TooFewArcs,
+    /// The first arc can only be 0, 1 or 2
+    FirstArcInvalid,
+    /// The second arc need to be less than 40 when the first arc is 0 or 1
+    SecondArcInvalid,
+}
+
+impl fmt::Display for OIDParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OIDParseError::ArcEmpty => write!(f, "empty arc"),
+            OIDParseError::ArcInvalidCharacter(c) => {
+                write!(f, "invalid character {:?} in arc", *c as char)
             }
+            OIDParseError::ArcOverflow => write!(f, "arc too big"),
+            OIDParseError::TooFewArcs => write!(f, "too few arcs"),
+            OIDParseError::FirstArcInvalid => write!(f, "first arc not 0, 1 or 2"),
+            OIDParseError::SecondArcInvalid => write!(f, "second arc not less than 40"),
         }
-        // this really cannot happen, but check for extra safety
-        if index > slice.len() {
-            return Err(());
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OIDParseError {}
+
+// parse the decimal arc starting at pos in the dotted notation,
+// and return the arc and the position of the next arc
+const fn dotted_next_arc(s: &[u8], pos: usize) -> Result<(u128, usize), OIDParseError> {
+    let mut i = pos;
+    let mut acc: u128 = 0;
+    while i < s.len() && s[i] != b'.' {
+        let c = s[i];
+        if c < b'0' || c > b'9' {
+            return Err(OIDParseError::ArcInvalidCharacter(c));
+        }
+        acc = match acc.checked_mul(10) {
+            None => return Err(OIDParseError::ArcOverflow),
+            Some(v) => match v.checked_add((c - b'0') as u128) {
+                None => return Err(OIDParseError::ArcOverflow),
+                Some(v) => v,
+            },
+        };
+        i += 1;
+    }
+    if i == pos {
+        return Err(OIDParseError::ArcEmpty);
+    }
+    if i < s.len() {
+        // skip the dot, which cannot terminate the OID
+        i += 1;
+        if i == s.len() {
+            return Err(OIDParseError::ArcEmpty);
+        }
+    }
+    Ok((acc, i))
+}
+
+// the first subidentifier combine the first two arcs
+const fn first_subidentifier(arc1: u128, arc2: u128) -> Result<u128, OIDParseError> {
+    if arc1 > 2 {
+        return Err(OIDParseError::FirstArcInvalid);
+    }
+    if arc1 < 2 && arc2 >= 40 {
+        return Err(OIDParseError::SecondArcInvalid);
+    }
+    match arc2.checked_add(arc1 * 40) {
+        None => Err(OIDParseError::ArcOverflow),
+        Some(v) => Ok(v),
+    }
+}
+
+// number of bytes to encode the subidentifier in base 128
+const fn subidentifier_size(v: u128) -> usize {
+    let mut sz = 1;
+    let mut v = v >> 7;
+    while v > 0 {
+        sz += 1;
+        v >>= 7;
+    }
+    sz
+}
+
+// parse the first two arcs of the dotted notation, and return the first subidentifier
+// and the position of the next arc
+const fn dotted_first_subidentifier(s: &[u8]) -> Result<(u128, usize), OIDParseError> {
+    let (arc1, pos) = match dotted_next_arc(s, 0) {
+        Err(e) => return Err(e),
+        Ok(r) => r,
+    };
+    if pos == s.len() {
+        return Err(OIDParseError::TooFewArcs);
+    }
+    let (arc2, pos) = match dotted_next_arc(s, pos) {
+        Err(e) => return Err(e),
+        Ok(r) => r,
+    };
+    match first_subidentifier(arc1, arc2) {
+        Err(e) => Err(e),
+        Ok(v) => Ok((v, pos)),
+    }
+}
+
+/// Size of the encoding of the OID in dotted notation, used by the `oid!` macro
+#[doc(hidden)]
+pub const fn __oid_encoded_size(s: &str) -> usize {
+    let s = s.as_bytes();
+    let (first, mut pos) = match dotted_first_subidentifier(s) {
+        Err(_) => panic!("invalid OID"),
+        Ok(r) => r,
+    };
+    let mut sz = subidentifier_size(first);
+    while pos < s.len() {
+        let (arc, next) = match dotted_next_arc(s, pos) {
+            Err(_) => panic!("invalid OID"),
+            Ok(r) => r,
+        };
+        sz += subidentifier_size(arc);
+        pos = next;
+    }
+    sz
+}
+
+/// Encoding of the OID in dotted notation, used by the `oid!` macro
+#[doc(hidden)]
+pub const fn __oid_encode<const N: usize>(s: &str) -> [u8; N] {
+    let s = s.as_bytes();
+    let mut out = [0u8; N];
+    let (mut subid, mut pos) = match dotted_first_subidentifier(s) {
+        Err(_) => panic!("invalid OID"),
+        Ok(r) => r,
+    };
+    let mut index = 0;
+    loop {
+        let sz = subidentifier_size(subid);
+        let mut i = 0;
+        while i < sz {
+            let limb = ((subid >> (7 * (sz - 1 - i))) & 0x7f) as u8;
+            out[index + i] = if i == sz - 1 { limb } else { limb | 0x80 };
+            i += 1;
+        }
+        index += sz;
+        if pos == s.len() {
+            break;
+        }
+        let (arc, next) = match dotted_next_arc(s, pos) {
+            Err(_) => panic!("invalid OID"),
+            Ok(r) => r,
+        };
+        subid = arc;
+        pos = next;
+    }
+    out
+}
+
+/// Create a `&'static OID` from its dotted notation, checked at compile time
+///
+/// ```
+/// let ec_public_key = basn1::oid!("1.2.840.10045.2.1");
+/// assert_eq!(ec_public_key.as_ref(), &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01]);
+/// ```
+#[macro_export]
+macro_rules! oid {
+    ($s: expr) => {{
+        const SIZE: usize = $crate::__oid_encoded_size($s);
+        const BYTES: [u8; SIZE] = $crate::__oid_encode::<SIZE>($s);
+        const VALUE: &'static $crate::OID = $crate::OID::__from_static(&BYTES);
+        VALUE
+    }};
+}
+
+#[cfg(feature = "owned")]
+fn push_subidentifier(out: &mut Vec<u8>, v: u128) {
+    let sz = subidentifier_size(v);
+    for i in 0..sz {
+        let limb = ((v >> (7 * (sz - 1 - i))) & 0x7f) as u8;
+        out.push(if i == sz - 1 { limb } else { limb | 0x80 });
+    }
+}
+
+#[cfg(feature = "owned")]
+impl OIDOwned {
+    /// Create an OID from all its arcs, including the first two
+    pub fn from_arcs(arcs: &[u128]) -> Result<Self, OIDParseError> {
+        if arcs.len() < 2 {
+            return Err(OIDParseError::TooFewArcs);
+        }
+        let mut out = Vec::new();
+        push_subidentifier(&mut out, first_subidentifier(arcs[0], arcs[1])?);
+        for arc in &arcs[2..] {
+            push_subidentifier(&mut out, *arc);
+        }
+        Ok(OIDOwned(out))
+    }
+}
+
+#[cfg(feature = "owned")]
+impl core::str::FromStr for OIDOwned {
+    type Err = OIDParseError;
+
+    /// Parse an OID in the dotted notation (e.g. `1.2.840.10045.2.1`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.as_bytes();
+        let (first, mut pos) = dotted_first_subidentifier(s)?;
+        let mut out = Vec::new();
+        push_subidentifier(&mut out, first);
+        while pos < s.len() {
+            let (arc, next) = dotted_next_arc(s, pos)?;
+            push_subidentifier(&mut out, arc);
+            pos = next;
+        }
+        Ok(OIDOwned(out))
+    }
+}
+
+impl RelativeOID {
+    /// Return all the components of the relative OID
+    pub fn components(&self) -> OIDComponents<'_> {
+        OIDComponents {
+            slice: &self.0,
+            index: 0,
         }
+    }
 
+    #[allow(clippy::result_unit_err)]
+    pub fn parse_from_slice(slice: &[u8]) -> Result<&Self, ()> {
+        check_subidentifiers(slice)?;
         Ok(Self::from_raw_slice(slice))
     }
 }
 
+impl fmt::Display for RelativeOID {
+    /// Display the relative OID in the dotted decimal notation (e.g. `8.1`)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, component) in self.components().enumerate() {
+            if i > 0 {
+                write!(f, ".")?;
+            }
+            // all the components have been checked to fit when parsing
+            write!(f, "{}", component.to_u128().unwrap())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "owned")]
+impl RelativeOIDOwned {
+    /// Create a relative OID from all its arcs
+    pub fn from_arcs(arcs: &[u128]) -> Result<Self, OIDParseError> {
+        if arcs.is_empty() {
+            return Err(OIDParseError::TooFewArcs);
+        }
+        let mut out = Vec::new();
+        for arc in arcs {
+            push_subidentifier(&mut out, *arc);
+        }
+        Ok(RelativeOIDOwned(out))
+    }
+}
+
+#[cfg(feature = "owned")]
+impl OIDOwned {
+    /// Append all the arcs of the relative OID to the OID
+    pub fn append(&mut self, relative: &RelativeOID) {
+        self.0.extend_from_slice(&relative.0)
+    }
+}
+
 impl<'a> Iterator for OIDComponents<'a> {
     type Item = &'a OIDComponent;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index == self.slice.0.len() {
+        if self.index == self.slice.len() {
             None
         } else {
-            match IntegerContBit7::parse_from_slice(&self.slice.0[self.index..]) {
+            match IntegerContBit7::parse_from_slice(&self.slice[self.index..]) {
                 Err(_) => unreachable!(),
                 Ok((r, adv)) => {
                     self.index += adv;
@@ -150,7 +547,1115 @@ impl<'a> Iterator for OIDComponents<'a> {
 }
 
 impl Integer {
+    #[allow(clippy::result_unit_err)]
+    pub fn from_slice(slice: &[u8]) -> Result<&Self, ()> {
+        Integer8Bit::from_slice(slice).map(Self::from_inner_slice)
+    }
+}
+
+impl<'a> Iterator for OIDArcs<'a> {
+    type Item = u128;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(second) = self.second.take() {
+            return Some(second);
+        }
+        if self.components.index == 0 {
+            let (value1, value2) = self.oid.first_arcs();
+            let (_, index) = self.oid.first_subidentifier();
+            self.components.index = index;
+            self.second = Some(value2);
+            return Some(value1 as u128);
+        }
+        // all the components have been checked to fit when parsing
+        self.components.next().map(|c| c.to_u128().unwrap())
+    }
+}
+
+macro_rules! ascii_string {
+    ($name: ident, $valid: expr, $alphabet: expr) => {
+        impl $name {
+            #[doc = concat!("Try to parse from a slice, which can only contains ", $alphabet)]
+            #[allow(clippy::result_unit_err)]
+            pub fn from_slice(slice: &[u8]) -> Result<&Self, ()> {
+                if !slice.iter().all(|c| $valid(*c)) {
+                    return Err(());
+                }
+                Ok(Self::from_raw_slice(slice))
+            }
+
+            /// Return the string
+            pub fn as_str(&self) -> &str {
+                // only ascii characters have been validated
+                core::str::from_utf8(&self.0).unwrap()
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+fn is_numeric(c: u8) -> bool {
+    c.is_ascii_digit() || c == b' '
+}
+
+fn is_printable(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || b" '()+,-./:=?".contains(&c)
+}
+
+ascii_string!(NumericString, is_numeric, "digits and space");
+ascii_string!(
+    PrintableString,
+    is_printable,
+    "latin letters, digits, space and the symbols `'()+,-./:=?`"
+);
+ascii_string!(IA5String, |c: u8| c.is_ascii(), "7 bits characters");
+ascii_string!(
+    VisibleString,
+    |c: u8| (0x20..0x7f).contains(&c),
+    "printable 7 bits characters"
+);
+
+macro_rules! chars_display {
+    ($name: ident) => {
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                use core::fmt::Write;
+                for c in self.chars() {
+                    f.write_char(c)?;
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+impl T61String {
+    /// Create from a slice, where any byte is accepted
+    pub fn from_slice(slice: &[u8]) -> &Self {
+        Self::from_raw_slice(slice)
+    }
+
+    /// Return the characters of the string
+    ///
+    /// The T.61 character set is rarely used as defined, and like most implementations,
+    /// the bytes are interpreted as latin-1 (ISO 8859-1) characters
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.0.iter().map(|c| *c as char)
+    }
+}
+
+chars_display!(T61String);
+
+impl UniversalString {
+    /// Try to parse from a slice, which need to be UCS-4 big endian characters
+    #[allow(clippy::result_unit_err)]
+    pub fn from_slice(slice: &[u8]) -> Result<&Self, ()> {
+        if slice.len() % 4 != 0 {
+            return Err(());
+        }
+        for c in slice.chunks(4) {
+            let v = u32::from_be_bytes([c[0], c[1], c[2], c[3]]);
+            if core::char::from_u32(v).is_none() {
+                return Err(());
+            }
+        }
+        Ok(Self::from_raw_slice(slice))
+    }
+
+    /// Return the characters of the string
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.0.chunks(4).map(|c| {
+            let v = u32::from_be_bytes([c[0], c[1], c[2], c[3]]);
+            core::char::from_u32(v).unwrap()
+        })
+    }
+}
+
+chars_display!(UniversalString);
+
+impl BMPString {
+    /// Try to parse from a slice, which need to be UCS-2 big endian characters
+    #[allow(clippy::result_unit_err)]
     pub fn from_slice(slice: &[u8]) -> Result<&Self, ()> {
-        Integer8Bit::from_slice(slice).map(|u| Self::from_inner_slice(u))
+        if slice.len() % 2 != 0 {
+            return Err(());
+        }
+        for c in slice.chunks(2) {
+            let v = u16::from_be_bytes([c[0], c[1]]);
+            // surrogates are not characters in UCS-2
+            if (0xd800..0xe000).contains(&v) {
+                return Err(());
+            }
+        }
+        Ok(Self::from_raw_slice(slice))
+    }
+
+    /// Return the characters of the string
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.0.chunks(2).map(|c| {
+            let v = u16::from_be_bytes([c[0], c[1]]);
+            core::char::from_u32(v as u32).unwrap()
+        })
+    }
+}
+
+chars_display!(BMPString);
+
+/// Any of the ASN.1 character string types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnyString<'a> {
+    Utf8(&'a str),
+    Numeric(&'a NumericString),
+    Printable(&'a PrintableString),
+    T61(&'a T61String),
+    IA5(&'a IA5String),
+    Visible(&'a VisibleString),
+    Universal(&'a UniversalString),
+    BMP(&'a BMPString),
+}
+
+impl<'a> AnyString<'a> {
+    /// Return the string directly if it is encoded as UTF-8 or with an ASCII subset
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self {
+            AnyString::Utf8(s) => Some(s),
+            AnyString::Numeric(s) => Some(s.as_str()),
+            AnyString::Printable(s) => Some(s.as_str()),
+            AnyString::IA5(s) => Some(s.as_str()),
+            AnyString::Visible(s) => Some(s.as_str()),
+            AnyString::T61(_) | AnyString::Universal(_) | AnyString::BMP(_) => None,
+        }
+    }
+
+    /// Return the characters of the string, whatever its encoding
+    pub fn chars(&self) -> AnyStringChars<'a> {
+        AnyStringChars {
+            string: *self,
+            index: 0,
+        }
+    }
+}
+
+impl<'a> fmt::Display for AnyString<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use core::fmt::Write;
+        if let Some(s) = self.as_str() {
+            return f.write_str(s);
+        }
+        for c in self.chars() {
+            f.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over the characters of any string
+#[derive(Debug, Clone)]
+pub struct AnyStringChars<'a> {
+    string: AnyString<'a>,
+    index: usize,
+}
+
+impl<'a> Iterator for AnyStringChars<'a> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // the strings have all been validated, so each character is valid
+        let (c, size) = match self.string {
+            AnyString::Utf8(s) => {
+                let c = s[self.index..].chars().next()?;
+                (c, c.len_utf8())
+            }
+            AnyString::Universal(s) => {
+                let b = s.0.get(self.index..self.index + 4)?;
+                let v = u32::from_be_bytes([b[0], b[1], b[2], b[3]]);
+                (core::char::from_u32(v).unwrap(), 4)
+            }
+            AnyString::BMP(s) => {
+                let b = s.0.get(self.index..self.index + 2)?;
+                let v = u16::from_be_bytes([b[0], b[1]]);
+                (core::char::from_u32(v as u32).unwrap(), 2)
+            }
+            // ascii and latin-1 are mapped byte to character
+            AnyString::Numeric(s) => (*s.0.get(self.index)? as char, 1),
+            AnyString::Printable(s) => (*s.0.get(self.index)? as char, 1),
+            AnyString::T61(s) => (*s.0.get(self.index)? as char, 1),
+            AnyString::IA5(s) => (*s.0.get(self.index)? as char, 1),
+            AnyString::Visible(s) => (*s.0.get(self.index)? as char, 1),
+        };
+        self.index += size;
+        Some(c)
+    }
+}
+
+// maximum size of a decimal REAL, so that it can be converted with a stack buffer
+const REAL_DECIMAL_MAX_SIZE: usize = 128;
+
+// check the ISO 6093 number representation (1: NR1, 2: NR2, 3: NR3)
+fn is_iso6093(form: u8, s: &[u8]) -> bool {
+    let digits = |i: &mut usize| {
+        let start = *i;
+        while *i < s.len() && s[*i].is_ascii_digit() {
+            *i += 1;
+        }
+        *i - start
+    };
+    let sign = |i: &mut usize| {
+        if *i < s.len() && (s[*i] == b'+' || s[*i] == b'-') {
+            *i += 1;
+        }
+    };
+    let mut i = 0;
+    while i < s.len() && s[i] == b' ' {
+        i += 1;
+    }
+    sign(&mut i);
+    let int_digits = digits(&mut i);
+    if form == 1 {
+        return int_digits > 0 && i == s.len();
+    }
+    if i == s.len() || (s[i] != b'.' && s[i] != b',') {
+        return false;
+    }
+    i += 1;
+    if int_digits + digits(&mut i) == 0 {
+        return false;
+    }
+    if form == 2 {
+        return i == s.len();
+    }
+    if i == s.len() || (s[i] != b'e' && s[i] != b'E') {
+        return false;
+    }
+    i += 1;
+    sign(&mut i);
+    digits(&mut i) > 0 && i == s.len()
+}
+
+// check the restricted NR3 form of DER: no leading or trailing zeros on
+// the integer mantissa, and an exponent of +0 or without plus sign and leading zeros
+fn is_der_decimal(s: &[u8]) -> bool {
+    let s = s.strip_prefix(b"-").unwrap_or(s);
+    let dot = match s.iter().position(|c| *c == b'.') {
+        None => return false,
+        Some(dot) => dot,
+    };
+    let mantissa = &s[..dot];
+    if mantissa.is_empty()
+        || !mantissa.iter().all(|c| c.is_ascii_digit())
+        || mantissa[0] == b'0'
+        || mantissa[mantissa.len() - 1] == b'0'
+    {
+        return false;
+    }
+    let exponent = match s[dot + 1..].strip_prefix(b"E") {
+        None => return false,
+        Some(exponent) => exponent,
+    };
+    if exponent == b"+0" {
+        return true;
+    }
+    let exponent = exponent.strip_prefix(b"-").unwrap_or(exponent);
+    !exponent.is_empty() && exponent.iter().all(|c| c.is_ascii_digit()) && exponent[0] != b'0'
+}
+
+// split the binary encoding in the exponent and the mantissa octets
+fn real_binary_parts(s: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (start, len) = match s[0] & 0b11 {
+        0b11 => (2, *s.get(1)? as usize),
+        n => (1, n as usize + 1),
+    };
+    // there's at least one octet for the exponent and the mantissa
+    if len == 0 || s.len() <= start + len {
+        return None;
+    }
+    Some((&s[start..start + len], &s[start + len..]))
+}
+
+// the f64 nearest to m * 2^shift, with the ties rounded to even
+fn round_to_f64(m: u64, shift: i64) -> f64 {
+    if m == 0 {
+        return 0.0;
+    }
+    // normalize so that m is in [2^63, 2^64), and the value in [2^exp, 2^(exp+1))
+    let zeros = m.leading_zeros();
+    let m = m << zeros;
+    let exp = shift - zeros as i64 + 63;
+    if exp > 1023 {
+        return f64::INFINITY;
+    }
+    // low bits of m not fitting in the 53 bits significand, or less for a subnormal
+    let drop = if exp >= -1022 { 11 } else { 11 - 1022 - exp };
+    if drop > 64 {
+        return 0.0;
+    }
+    let m = m as u128;
+    let rest = m & ((1 << drop) - 1);
+    let half = 1 << (drop - 1);
+    let mut significand = (m >> drop) as u64;
+    if rest > half || (rest == half && significand & 1 == 1) {
+        significand += 1;
+    }
+    if exp < -1022 {
+        // a subnormal, which become the smallest normal when rounded up to 2^52
+        return f64::from_bits(significand);
+    }
+    let (significand, exp) = if significand == 1 << 53 {
+        (significand >> 1, exp + 1)
+    } else {
+        (significand, exp)
+    };
+    if exp > 1023 {
+        return f64::INFINITY;
+    }
+    f64::from_bits((((exp + 1023) as u64) << 52) | (significand & ((1 << 52) - 1)))
+}
+
+impl Real {
+    /// Try to parse from a slice, which can be any of the X.690 encodings:
+    ///
+    /// * empty for the zero value
+    /// * the special values PLUS-INFINITY, MINUS-INFINITY, NOT-A-NUMBER and minus zero
+    /// * the binary encoding in base 2, 8 or 16
+    /// * the decimal encoding with the ISO 6093 NR1, NR2 or NR3 forms, up to 128 characters
+    #[allow(clippy::result_unit_err)]
+    pub fn from_slice(slice: &[u8]) -> Result<&Self, ()> {
+        let valid = match slice.first() {
+            None => true,
+            Some(first) if first & 0x80 != 0 => {
+                // base 0b11 is reserved
+                (first >> 4) & 0b11 != 0b11 && real_binary_parts(slice).is_some()
+            }
+            Some(first) if first & 0x40 != 0 => slice.len() == 1 && *first <= 0x43,
+            Some(first) => {
+                slice.len() - 1 <= REAL_DECIMAL_MAX_SIZE
+                    && (1..=3).contains(first)
+                    && is_iso6093(*first, &slice[1..])
+            }
+        };
+        if !valid {
+            return Err(());
+        }
+        Ok(Self::from_raw_slice(slice))
+    }
+
+    /// Return if the encoding is the canonical DER encoding
+    ///
+    /// The binary encoding need to be in base 2 with a scale factor of 0, an odd mantissa,
+    /// and an exponent on the minimal number of octets, and the decimal encoding need
+    /// to use the restricted NR3 form (e.g. `-15.E-1`)
+    pub fn is_der_canonical(&self) -> bool {
+        let first = match self.0.first() {
+            None => return true,
+            Some(first) => *first,
+        };
+        if first & 0x80 != 0 {
+            let (exponent, mantissa) = real_binary_parts(&self.0).unwrap();
+            (first & 0b0011_1100) == 0
+                && (first & 0b11 != 0b11 || exponent.len() > 3)
+                && strip_redundant_sign_bytes(exponent).len() == exponent.len()
+                && mantissa[0] != 0
+                && mantissa[mantissa.len() - 1] & 1 == 1
+        } else if first & 0x40 != 0 {
+            true
+        } else {
+            first == 3 && is_der_decimal(&self.0[1..])
+        }
+    }
+
+    /// Return the nearest f64 of the value
+    ///
+    /// Nothing is returned if the decimal encoding cannot be converted
+    pub fn to_f64(&self) -> Option<f64> {
+        let first = match self.0.first() {
+            None => return Some(0.0),
+            Some(first) => *first,
+        };
+        if first & 0x80 != 0 {
+            let (exponent, mantissa) = real_binary_parts(&self.0).unwrap();
+            let base_bits = match (first >> 4) & 0b11 {
+                0 => 1,
+                1 => 3,
+                _ => 4,
+            };
+            let scale = ((first >> 2) & 0b11) as i64;
+            // exponents too big to fit saturate, as the value is already out of the f64 range
+            let mut e: i64 = if exponent[0] & 0x80 != 0 { -1 } else { 0 };
+            for b in exponent {
+                if e.abs() > (1 << 40) {
+                    break;
+                }
+                e = (e << 8) | (*b as i64);
+            }
+            // keep the most significant bits of the mantissa, and whether any of
+            // the dropped bits is set in the least significant bit, for the rounding
+            let mut m: u64 = 0;
+            let mut extra = 0;
+            let mut sticky = false;
+            for b in mantissa {
+                if m >> 56 != 0 {
+                    extra += 8;
+                    sticky |= *b != 0;
+                } else {
+                    m = (m << 8) | (*b as u64);
+                }
+            }
+            if sticky {
+                m |= 1;
+            }
+            let shift = (e * base_bits + scale + extra).clamp(-3000, 3000);
+            let v = round_to_f64(m, shift);
+            Some(if first & 0x40 != 0 { -v } else { v })
+        } else if first & 0x40 != 0 {
+            Some(match first {
+                0x40 => f64::INFINITY,
+                0x41 => f64::NEG_INFINITY,
+                0x42 => f64::NAN,
+                _ => -0.0,
+            })
+        } else {
+            let mut s = &self.0[1..];
+            while s.first() == Some(&b' ') {
+                s = &s[1..];
+            }
+            // ISO 6093 can use a comma as decimal mark
+            let mut buf = [0u8; REAL_DECIMAL_MAX_SIZE];
+            for (o, c) in buf.iter_mut().zip(s.iter()) {
+                *o = if *c == b',' { b'.' } else { *c };
+            }
+            core::str::from_utf8(&buf[..s.len()]).ok()?.parse().ok()
+        }
+    }
+
+    /// Create the canonical DER encoding of the value in the out buffer
+    pub fn from_f64(v: f64, out: &mut [u8; 10]) -> &Self {
+        let len = if v.is_nan() {
+            out[0] = 0x42;
+            1
+        } else if v.is_infinite() {
+            out[0] = if v > 0.0 { 0x40 } else { 0x41 };
+            1
+        } else if v == 0.0 {
+            if v.is_sign_negative() {
+                out[0] = 0x43;
+                1
+            } else {
+                0
+            }
+        } else {
+            let bits = v.to_bits();
+            let biased = ((bits >> 52) & 0x7ff) as i64;
+            let fraction = bits & ((1 << 52) - 1);
+            let (mut mantissa, mut exponent) = if biased == 0 {
+                (fraction, -1074)
+            } else {
+                (fraction | (1 << 52), biased - 1075)
+            };
+            // the mantissa need to be odd
+            let zeros = mantissa.trailing_zeros();
+            mantissa >>= zeros;
+            exponent += zeros as i64;
+
+            let exponent_bytes = (exponent as i16).to_be_bytes();
+            let exponent_bytes = strip_redundant_sign_bytes(&exponent_bytes);
+            let sign = if v < 0.0 { 0x40 } else { 0 };
+            out[0] = 0x80 | sign | (exponent_bytes.len() as u8 - 1);
+            let mut len = 1;
+            out[len..len + exponent_bytes.len()].copy_from_slice(exponent_bytes);
+            len += exponent_bytes.len();
+            let mantissa_bytes = mantissa.to_be_bytes();
+            let skip = mantissa.leading_zeros() as usize / 8;
+            out[len..len + 8 - skip].copy_from_slice(&mantissa_bytes[skip..]);
+            len + 8 - skip
+        };
+        Self::from_raw_slice(&out[..len])
+    }
+}
+
+// value of 2 ascii decimal digits
+fn digits2(s: &[u8]) -> Option<u8> {
+    if s[0].is_ascii_digit() && s[1].is_ascii_digit() {
+        Some((s[0] - b'0') * 10 + (s[1] - b'0'))
+    } else {
+        None
+    }
+}
+
+// write the value as fixed size ascii decimal digits
+fn write_digits(out: &mut [u8], mut v: u32) {
+    for c in out.iter_mut().rev() {
+        *c = b'0' + (v % 10) as u8;
+        v /= 10;
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// check the "MMDDHHMMSS" part following the year
+fn check_date_time(year: u16, s: &[u8]) -> Result<(), ()> {
+    let mut v = [0u8; 5];
+    for (i, x) in v.iter_mut().enumerate() {
+        *x = digits2(&s[i * 2..]).ok_or(())?;
+    }
+    let [month, day, hour, minute, second] = v;
+    if month == 0 || month > 12 || day == 0 || day > days_in_month(year, month) {
+        return Err(());
+    }
+    if hour > 23 || minute > 59 || second > 59 {
+        return Err(());
+    }
+    Ok(())
+}
+
+// number of days since 1970-01-01 of a date in the proleptic gregorian calendar
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// date (year, month, day) of a number of days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// write "MMDDHHMMSS" of the unix timestamp, and return the year
+fn write_date_time(timestamp: i64, out: &mut [u8]) -> i64 {
+    let days = timestamp.div_euclid(86400);
+    let secs = timestamp.rem_euclid(86400) as u32;
+    let (year, month, day) = civil_from_days(days);
+    write_digits(&mut out[0..2], month as u32);
+    write_digits(&mut out[2..4], day as u32);
+    write_digits(&mut out[4..6], secs / 3600);
+    write_digits(&mut out[6..8], (secs / 60) % 60);
+    write_digits(&mut out[8..10], secs % 60);
+    year
+}
+
+macro_rules! time_accessors {
+    ($name: ident, $year_size: expr) => {
+        impl $name {
+            /// Return the time as a string
+            pub fn as_str(&self) -> &str {
+                // only ascii characters have been validated
+                core::str::from_utf8(&self.0).unwrap()
+            }
+
+            /// Return the month, from 1 to 12
+            pub fn month(&self) -> u8 {
+                digits2(&self.0[$year_size..]).unwrap()
+            }
+
+            /// Return the day of the month, from 1 to 31
+            pub fn day(&self) -> u8 {
+                digits2(&self.0[$year_size + 2..]).unwrap()
+            }
+
+            /// Return the hour, from 0 to 23
+            pub fn hour(&self) -> u8 {
+                digits2(&self.0[$year_size + 4..]).unwrap()
+            }
+
+            /// Return the minute, from 0 to 59
+            pub fn minute(&self) -> u8 {
+                digits2(&self.0[$year_size + 6..]).unwrap()
+            }
+
+            /// Return the second, from 0 to 59
+            pub fn second(&self) -> u8 {
+                digits2(&self.0[$year_size + 8..]).unwrap()
+            }
+
+            /// Return the number of seconds since the unix epoch (1970-01-01T00:00:00Z),
+            /// ignoring the fraction of second
+            pub fn to_unix_timestamp(&self) -> i64 {
+                let days = days_from_civil(self.year() as i64, self.month(), self.day());
+                days * 86400
+                    + self.hour() as i64 * 3600
+                    + self.minute() as i64 * 60
+                    + self.second() as i64
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+time_accessors!(UtcTime, 2);
+time_accessors!(GeneralizedTime, 4);
+
+impl UtcTime {
+    /// Try to parse from a slice
+    ///
+    /// DER only allows the `YYMMDDHHMMSSZ` format, where the years
+    /// from 50 to 99 are 1950 to 1999, and the years from 00 to 49 are 2000 to 2049
+    #[allow(clippy::result_unit_err)]
+    pub fn from_slice(slice: &[u8]) -> Result<&Self, ()> {
+        if slice.len() != 13 || slice[12] != b'Z' {
+            return Err(());
+        }
+        let yy = digits2(slice).ok_or(())?;
+        check_date_time(Self::full_year(yy), &slice[2..12])?;
+        Ok(Self::from_raw_slice(slice))
+    }
+
+    fn full_year(yy: u8) -> u16 {
+        if yy >= 50 {
+            1900 + yy as u16
+        } else {
+            2000 + yy as u16
+        }
+    }
+
+    /// Return the year, from 1950 to 2049
+    pub fn year(&self) -> u16 {
+        Self::full_year(digits2(&self.0).unwrap())
+    }
+
+    /// Create the time of the unix timestamp in the out buffer
+    ///
+    /// Nothing is returned if the year is not representable, which is before 1950 or after 2049
+    pub fn from_unix_timestamp(timestamp: i64, out: &mut [u8; 13]) -> Option<&Self> {
+        let year = write_date_time(timestamp, &mut out[2..12]);
+        if !(1950..=2049).contains(&year) {
+            return None;
+        }
+        write_digits(&mut out[0..2], (year % 100) as u32);
+        out[12] = b'Z';
+        Some(Self::from_raw_slice(&out[..]))
+    }
+}
+
+impl GeneralizedTime {
+    /// Try to parse from a slice
+    ///
+    /// DER only allows the `YYYYMMDDHHMMSS[.f]Z` format, where the optional
+    /// fraction of second cannot have trailing zeros
+    #[allow(clippy::result_unit_err)]
+    pub fn from_slice(slice: &[u8]) -> Result<&Self, ()> {
+        if slice.len() < 15 || slice[slice.len() - 1] != b'Z' {
+            return Err(());
+        }
+        let year = digits2(slice).ok_or(())? as u16 * 100 + digits2(&slice[2..]).ok_or(())? as u16;
+        check_date_time(year, &slice[4..14])?;
+        if slice.len() > 15 {
+            let fraction = &slice[15..slice.len() - 1];
+            if slice[14] != b'.'
+                || fraction.is_empty()
+                || !fraction.iter().all(|c| c.is_ascii_digit())
+                || fraction[fraction.len() - 1] == b'0'
+            {
+                return Err(());
+            }
+        }
+        Ok(Self::from_raw_slice(slice))
+    }
+
+    /// Return the year, from 0 to 9999
+    pub fn year(&self) -> u16 {
+        digits2(&self.0).unwrap() as u16 * 100 + digits2(&self.0[2..]).unwrap() as u16
+    }
+
+    /// Return the fraction of second in nanoseconds,
+    /// where the digits after the nanoseconds are ignored
+    pub fn nanos(&self) -> u32 {
+        if self.0.len() == 15 {
+            return 0;
+        }
+        let fraction = &self.0[15..self.0.len() - 1];
+        let mut nanos = 0;
+        for i in 0..9 {
+            let digit = fraction.get(i).map(|c| c - b'0').unwrap_or(0);
+            nanos = nanos * 10 + digit as u32;
+        }
+        nanos
+    }
+
+    /// Create the time of the unix timestamp in the out buffer, without fraction of second
+    ///
+    /// Nothing is returned if the year is not representable, which is before 0 or after 9999
+    pub fn from_unix_timestamp(timestamp: i64, out: &mut [u8; 15]) -> Option<&Self> {
+        // avoid overflowing the computation for timestamps out of range
+        if !(-62_167_219_200..=253_402_300_799).contains(&timestamp) {
+            return None;
+        }
+        let year = write_date_time(timestamp, &mut out[4..14]);
+        write_digits(&mut out[0..4], year as u32);
+        out[14] = b'Z';
+        Some(Self::from_raw_slice(&out[..]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::ToString;
+    use std::vec::Vec;
+
+    #[test]
+    fn oid_macro() {
+        let oid = oid!("1.2.840.10045.2.1");
+        assert_eq!(oid.as_ref(), &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01]);
+        assert_eq!(oid.to_string(), "1.2.840.10045.2.1");
+        const CN: &OID = oid!("2.5.4.3");
+        assert_eq!(CN.as_ref(), &[0x55, 0x04, 0x03]);
+        assert_eq!(oid!("0.0").as_ref(), &[0x00]);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid OID encoding")]
+    fn oid_from_static_invalid() {
+        // the last subidentifier is truncated
+        OID::__from_static(&[0x2a, 0x86]);
+    }
+
+    #[test]
+    fn oid_arcs() {
+        let oid = OID::parse_from_slice(&[0x88, 0x37, 0x03]).unwrap();
+        assert_eq!(oid.value1(), 2);
+        assert_eq!(oid.value2(), 999);
+        assert_eq!(oid.arcs().collect::<Vec<_>>(), vec![2, 999, 3]);
+        let components: Vec<_> = oid.components().map(|c| c.to_u128()).collect();
+        assert_eq!(components, vec![Some(3)]);
+        assert_eq!(oid.to_string(), "2.999.3");
+        assert_eq!(oid, oid!("2.999.3"));
+
+        let oid = oid!("1.39.5");
+        assert_eq!(oid.arcs().collect::<Vec<_>>(), vec![1, 39, 5]);
+        let oid = oid!("0.0");
+        assert_eq!(oid.arcs().collect::<Vec<_>>(), vec![0, 0]);
+
+        // the first subidentifier cannot have a leading zero limb
+        assert!(OID::parse_from_slice(&[0x80, 0x01]).is_err());
+        // subidentifier overflowing 128 bits
+        let mut big = [0xff; 20];
+        big[19] = 0x7f;
+        assert!(OID::parse_from_slice(&big).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "owned")]
+    fn oid_owned() {
+        let oid: OIDOwned = "1.2.840.113549.1.1.11".parse().unwrap();
+        assert_eq!(oid.as_ref(), oid!("1.2.840.113549.1.1.11").as_ref());
+        let oid2 = OIDOwned::from_arcs(&[1, 2, 840, 113549, 1, 1, 11]).unwrap();
+        assert_eq!(oid, oid2);
+
+        for (s, err) in [
+            ("", OIDParseError::ArcEmpty),
+            ("1", OIDParseError::TooFewArcs),
+            ("1.", OIDParseError::ArcEmpty),
+            ("1..2", OIDParseError::ArcEmpty),
+            ("1.2.", OIDParseError::ArcEmpty),
+            ("1.a", OIDParseError::ArcInvalidCharacter(b'a')),
+            ("3.1", OIDParseError::FirstArcInvalid),
+            ("1.40", OIDParseError::SecondArcInvalid),
+            (
+                "1.2.340282366920938463463374607431768211456",
+                OIDParseError::ArcOverflow,
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(s.parse::<OIDOwned>(), Err(*err), "{}", s);
+        }
+        assert_eq!(OIDOwned::from_arcs(&[2]), Err(OIDParseError::TooFewArcs));
+    }
+
+    #[test]
+    fn utc_time() {
+        let t = UtcTime::from_slice(b"491231235959Z").unwrap();
+        assert_eq!(t.year(), 2049);
+        assert_eq!((t.month(), t.day()), (12, 31));
+        assert_eq!((t.hour(), t.minute(), t.second()), (23, 59, 59));
+        assert_eq!(t.to_unix_timestamp(), 2524607999);
+
+        let t = UtcTime::from_slice(b"500101000000Z").unwrap();
+        assert_eq!(t.year(), 1950);
+        assert_eq!(t.to_unix_timestamp(), -631152000);
+        assert!(UtcTime::from_slice(b"000229000000Z").is_ok());
+
+        for invalid in &[
+            &b"0001010000Z"[..],
+            b"000101000000",
+            b"0001010000000Z",
+            b"000101000000+0000",
+            b"010229000000Z",
+            b"001301000000Z",
+            b"000100000000Z",
+            b"000101240000Z",
+            b"000101006000Z",
+            b"000101000060Z",
+            b"00010100000aZ",
+        ] {
+            assert!(UtcTime::from_slice(invalid).is_err(), "{:?}", invalid);
+        }
+
+        let mut buf = [0u8; 13];
+        let t = UtcTime::from_unix_timestamp(946684800, &mut buf).unwrap();
+        assert_eq!(t.as_str(), "000101000000Z");
+        let mut buf = [0u8; 13];
+        assert!(UtcTime::from_unix_timestamp(2524608000, &mut buf).is_none());
+    }
+
+    #[test]
+    fn generalized_time() {
+        let t = GeneralizedTime::from_slice(b"19700101000000Z").unwrap();
+        assert_eq!(t.year(), 1970);
+        assert_eq!(t.nanos(), 0);
+        assert_eq!(t.to_unix_timestamp(), 0);
+
+        let t = GeneralizedTime::from_slice(b"21000228235959.12Z").unwrap();
+        assert_eq!(t.nanos(), 120_000_000);
+        assert_eq!(t.to_unix_timestamp(), 4107542399);
+        let t = GeneralizedTime::from_slice(b"00000101000000.1234567891Z").unwrap();
+        assert_eq!(t.nanos(), 123_456_789);
+        assert_eq!(t.to_unix_timestamp(), -62167219200);
+
+        for invalid in &[
+            &b"197001010000Z"[..],
+            b"19700101000000",
+            b"19700101000000.Z",
+            b"19700101000000.10Z",
+            b"19700101000000,1Z",
+            b"19700101000000.1+0100",
+            b"21000229000000Z",
+        ] {
+            assert!(
+                GeneralizedTime::from_slice(invalid).is_err(),
+                "{:?}",
+                invalid
+            );
+        }
+
+        let mut buf = [0u8; 15];
+        let t = GeneralizedTime::from_unix_timestamp(4107542399, &mut buf).unwrap();
+        assert_eq!(t.as_str(), "21000228235959Z");
+        let mut buf = [0u8; 15];
+        let t = GeneralizedTime::from_unix_timestamp(-1, &mut buf).unwrap();
+        assert_eq!(t.to_string(), "19691231235959Z");
+        assert!(GeneralizedTime::from_unix_timestamp(253402300800, &mut buf).is_none());
+    }
+
+    #[test]
+    fn restricted_strings() {
+        assert_eq!(
+            PrintableString::from_slice(b"Test CA (1), Inc.").map(|s| s.as_str()),
+            Ok("Test CA (1), Inc.")
+        );
+        assert!(PrintableString::from_slice(b"a@b").is_err());
+        assert!(PrintableString::from_slice(b"a*b").is_err());
+        assert!(NumericString::from_slice(b"0123 456").is_ok());
+        assert!(NumericString::from_slice(b"12a").is_err());
+        assert!(IA5String::from_slice(b"user@example.com").is_ok());
+        assert!(IA5String::from_slice(b"\x80").is_err());
+        assert!(VisibleString::from_slice(b"~").is_ok());
+        assert!(VisibleString::from_slice(b"\n").is_err());
+
+        assert_eq!(T61String::from_slice(b"caf\xe9").to_string(), "café");
+
+        let bmp = BMPString::from_slice(b"\x00c\x00a\x00f\x00\xe9\x20\xac").unwrap();
+        assert_eq!(bmp.to_string(), "café€");
+        assert!(BMPString::from_slice(b"\x00c\x00").is_err());
+        assert!(BMPString::from_slice(b"\xd8\x00").is_err());
+
+        let universal = UniversalString::from_slice(b"\x00\x00\x00h\x00\x01\xf6\x00").unwrap();
+        assert_eq!(
+            universal.chars().collect::<Vec<_>>(),
+            vec!['h', '\u{1f600}']
+        );
+        assert!(UniversalString::from_slice(b"\x00\x00\x00").is_err());
+        assert!(UniversalString::from_slice(b"\x00\x11\x00\x00").is_err());
+    }
+
+    #[test]
+    fn any_string() {
+        let bmp = BMPString::from_slice(b"\x00c\x00a\x00f\x00\xe9").unwrap();
+        let any = AnyString::BMP(bmp);
+        assert_eq!(any.as_str(), None);
+        assert_eq!(any.to_string(), "café");
+
+        let any = AnyString::Utf8("dé€");
+        assert_eq!(any.chars().collect::<Vec<_>>(), vec!['d', 'é', '€']);
+        let any = AnyString::T61(T61String::from_slice(b"\xe9t\xe9"));
+        assert_eq!(any.to_string(), "été");
+        let any = AnyString::Printable(PrintableString::from_slice(b"CA").unwrap());
+        assert_eq!(any.as_str(), Some("CA"));
+        assert_eq!(any.chars().count(), 2);
+    }
+
+    #[test]
+    fn real_binary() {
+        let mut buf = [0u8; 10];
+        for (v, expected) in [
+            (0.0, &b""[..]),
+            (-0.0, b"\x43"),
+            (f64::INFINITY, b"\x40"),
+            (f64::NEG_INFINITY, b"\x41"),
+            (1.0, b"\x80\x00\x01"),
+            (0.5, b"\x80\xff\x01"),
+            (-2.5, b"\xc0\xff\x05"),
+            (1024.0, b"\x80\x0a\x01"),
+            (f64::from_bits((1023 + 200) << 52), b"\x81\x00\xc8\x01"),
+        ]
+        .iter()
+        {
+            let r = Real::from_f64(*v, &mut buf);
+            assert_eq!(r.as_ref(), *expected, "{}", v);
+            assert!(r.is_der_canonical());
+            let r = Real::from_slice(expected).unwrap();
+            assert_eq!(r.to_f64().unwrap().to_bits(), v.to_bits());
+        }
+        assert!(Real::from_f64(f64::NAN, &mut buf)
+            .to_f64()
+            .unwrap()
+            .is_nan());
+
+        for v in &[
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            5e-324,
+            -1.0e-300,
+            123.456,
+            1e300,
+        ] {
+            let r = Real::from_f64(*v, &mut buf);
+            assert!(r.is_der_canonical());
+            assert_eq!(Real::from_slice(r.as_ref()).unwrap().to_f64(), Some(*v));
+        }
+
+        // base 8 and 16, with scale factor: 3 * 2^1 * 16^1
+        let r = Real::from_slice(b"\xa4\x01\x03").unwrap();
+        assert_eq!(r.to_f64(), Some(96.0));
+        assert!(!r.is_der_canonical());
+        let r = Real::from_slice(b"\x90\xff\x01").unwrap();
+        assert_eq!(r.to_f64(), Some(0.125));
+        // huge exponent with the length octet
+        let r = Real::from_slice(b"\x83\x04\x7f\xff\xff\xff\x01").unwrap();
+        assert_eq!(r.to_f64(), Some(f64::INFINITY));
+        assert!(r.is_der_canonical());
+
+        // rounding to the nearest, including the bits past the first 64 of the mantissa:
+        // 2^69 + 2^16 + 1 is just above the middle of 2^69 and 2^69 + 2^17
+        let r = Real::from_slice(b"\x80\x00\x20\x00\x00\x00\x00\x00\x01\x00\x01").unwrap();
+        assert_eq!(r.to_f64(), Some(f64::from_bits((1023 + 69) << 52 | 1)));
+        // 2^53 + 1 is a tie, rounded to the even 2^53
+        let r = Real::from_slice(b"\x80\x00\x20\x00\x00\x00\x00\x00\x01").unwrap();
+        assert_eq!(r.to_f64(), Some(9007199254740992.0));
+        // subnormals: 2^-1075 is a tie rounded to zero, and 3 * 2^-1076 is above
+        let r = Real::from_slice(b"\x81\xfb\xcd\x01").unwrap();
+        assert_eq!(r.to_f64(), Some(0.0));
+        let r = Real::from_slice(b"\x81\xfb\xcc\x03").unwrap();
+        assert_eq!(r.to_f64(), Some(5e-324));
+
+        // not canonical: even mantissa, non minimal exponent
+        assert!(!Real::from_slice(b"\x80\x00\x02")
+            .unwrap()
+            .is_der_canonical());
+        assert!(!Real::from_slice(b"\x81\x00\x00\x01")
+            .unwrap()
+            .is_der_canonical());
+        assert!(!Real::from_slice(b"\x83\x01\x00\x01")
+            .unwrap()
+            .is_der_canonical());
+
+        // invalid: reserved base, no mantissa, unknown special value
+        assert!(Real::from_slice(b"\xb0\x00\x01").is_err());
+        assert!(Real::from_slice(b"\x80\x00").is_err());
+        assert!(Real::from_slice(b"\x83\x00\x01").is_err());
+        assert!(Real::from_slice(b"\x44").is_err());
+        assert!(Real::from_slice(b"\x40\x00").is_err());
+    }
+
+    #[test]
+    fn real_decimal() {
+        for (data, v, canonical) in [
+            (&b"\x01  -12"[..], -12.0, false),
+            (b"\x02+1,5", 1.5, false),
+            (b"\x02.5", 0.5, false),
+            (b"\x03-15.E-1", -1.5, true),
+            (b"\x031.E+0", 1.0, true),
+            (b"\x0325.E3", 25000.0, true),
+            (b"\x0310.E+0", 10.0, false),
+            (b"\x031.E+1", 10.0, false),
+            (b"\x031.E01", 10.0, false),
+            (b"\x031.5E1", 15.0, false),
+        ]
+        .iter()
+        {
+            let r = Real::from_slice(data).unwrap();
+            assert_eq!(r.to_f64(), Some(*v), "{:?}", data);
+            assert_eq!(r.is_der_canonical(), *canonical, "{:?}", data);
+        }
+        for data in &[
+            &b"\x01"[..],
+            b"\x011.5",
+            b"\x02.",
+            b"\x021",
+            b"\x031.5",
+            b"\x031.5E",
+            b"\x04 1",
+            b"\x01inf",
+        ] {
+            assert!(Real::from_slice(data).is_err(), "{:?}", data);
+        }
+        // an unchecked encoding which is not a number doesn't convert
+        assert_eq!(Real::from_raw_slice(b"\x03one").to_f64(), None);
+    }
+
+    #[test]
+    fn relative_oid() {
+        let rel = RelativeOID::parse_from_slice(&[0x08, 0x86, 0x48, 0x01]).unwrap();
+        let components: Vec<_> = rel.components().map(|c| c.to_u64()).collect();
+        assert_eq!(components, vec![Some(8), Some(840), Some(1)]);
+        assert_eq!(rel.to_string(), "8.840.1");
+        assert!(RelativeOID::parse_from_slice(&[]).is_err());
+        assert!(RelativeOID::parse_from_slice(&[0x86]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "owned")]
+    fn relative_oid_append() {
+        let mut oid: OIDOwned = "1.3.6.1.4.1".parse().unwrap();
+        let rel = RelativeOIDOwned::from_arcs(&[1466, 115, 121, 1]).unwrap();
+        oid.append(&rel);
+        assert_eq!(oid.to_string(), "1.3.6.1.4.1.1466.115.121.1");
+        assert_eq!(
+            RelativeOIDOwned::from_arcs(&[]),
+            Err(OIDParseError::TooFewArcs)
+        );
     }
 }
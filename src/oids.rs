@@ -0,0 +1,186 @@
+//! Registry of well-known OIDs with their names
+//!
+//! Each OID is available as a constant, and the table can be searched by OID
+//! with `OID::name` or by name with `by_name`. The names are the ASN.1 names
+//! used in the defining RFCs, and the lookups don't allocate.
+//!
+//! ```
+//! use basn1::oids;
+//! assert_eq!(oids::EC_PUBLIC_KEY.name(), Some("ecPublicKey"));
+//! assert_eq!(oids::by_name("ecPublicKey"), Some(oids::EC_PUBLIC_KEY));
+//! ```
+
+use crate::objects::OID;
+
+macro_rules! oids {
+    ($($konst: ident = $dotted: expr, $name: expr;)*) => {
+        $(
+            #[doc = concat!("`", $name, "` (", $dotted, ")")]
+            pub const $konst: &OID = crate::oid!($dotted);
+        )*
+
+        static TABLE: &[(&OID, &str)] = &[$(($konst, $name)),*];
+    };
+}
+
+oids! {
+    // PKCS#1
+    RSA_ENCRYPTION = "1.2.840.113549.1.1.1", "rsaEncryption";
+    MD5_WITH_RSA_ENCRYPTION = "1.2.840.113549.1.1.4", "md5WithRSAEncryption";
+    SHA1_WITH_RSA_ENCRYPTION = "1.2.840.113549.1.1.5", "sha1WithRSAEncryption";
+    RSAES_OAEP = "1.2.840.113549.1.1.7", "id-RSAES-OAEP";
+    MGF1 = "1.2.840.113549.1.1.8", "id-mgf1";
+    RSASSA_PSS = "1.2.840.113549.1.1.10", "id-RSASSA-PSS";
+    SHA256_WITH_RSA_ENCRYPTION = "1.2.840.113549.1.1.11", "sha256WithRSAEncryption";
+    SHA384_WITH_RSA_ENCRYPTION = "1.2.840.113549.1.1.12", "sha384WithRSAEncryption";
+    SHA512_WITH_RSA_ENCRYPTION = "1.2.840.113549.1.1.13", "sha512WithRSAEncryption";
+
+    // PKIX algorithms
+    DSA = "1.2.840.10040.4.1", "id-dsa";
+    DSA_WITH_SHA1 = "1.2.840.10040.4.3", "id-dsa-with-sha1";
+    EC_PUBLIC_KEY = "1.2.840.10045.2.1", "ecPublicKey";
+    ECDSA_WITH_SHA1 = "1.2.840.10045.4.1", "ecdsa-with-SHA1";
+    ECDSA_WITH_SHA256 = "1.2.840.10045.4.3.2", "ecdsa-with-SHA256";
+    ECDSA_WITH_SHA384 = "1.2.840.10045.4.3.3", "ecdsa-with-SHA384";
+    ECDSA_WITH_SHA512 = "1.2.840.10045.4.3.4", "ecdsa-with-SHA512";
+    X25519 = "1.3.101.110", "id-X25519";
+    X448 = "1.3.101.111", "id-X448";
+    ED25519 = "1.3.101.112", "id-Ed25519";
+    ED448 = "1.3.101.113", "id-Ed448";
+    MD5 = "1.2.840.113549.2.5", "id-md5";
+    HMAC_WITH_SHA256 = "1.2.840.113549.2.9", "id-hmacWithSHA256";
+    SHA1 = "1.3.14.3.2.26", "id-sha1";
+    SHA256 = "2.16.840.1.101.3.4.2.1", "id-sha256";
+    SHA384 = "2.16.840.1.101.3.4.2.2", "id-sha384";
+    SHA512 = "2.16.840.1.101.3.4.2.3", "id-sha512";
+    SHA224 = "2.16.840.1.101.3.4.2.4", "id-sha224";
+    AES128_CBC = "2.16.840.1.101.3.4.1.2", "id-aes128-CBC";
+    AES192_CBC = "2.16.840.1.101.3.4.1.22", "id-aes192-CBC";
+    AES256_CBC = "2.16.840.1.101.3.4.1.42", "id-aes256-CBC";
+
+    // EC named curves
+    PRIME192V1 = "1.2.840.10045.3.1.1", "prime192v1";
+    PRIME256V1 = "1.2.840.10045.3.1.7", "prime256v1";
+    SECP256K1 = "1.3.132.0.10", "secp256k1";
+    SECP224R1 = "1.3.132.0.33", "secp224r1";
+    SECP384R1 = "1.3.132.0.34", "secp384r1";
+    SECP521R1 = "1.3.132.0.35", "secp521r1";
+
+    // X.520 attribute types
+    COMMON_NAME = "2.5.4.3", "commonName";
+    SURNAME = "2.5.4.4", "surname";
+    SERIAL_NUMBER = "2.5.4.5", "serialNumber";
+    COUNTRY_NAME = "2.5.4.6", "countryName";
+    LOCALITY_NAME = "2.5.4.7", "localityName";
+    STATE_OR_PROVINCE_NAME = "2.5.4.8", "stateOrProvinceName";
+    STREET_ADDRESS = "2.5.4.9", "streetAddress";
+    ORGANIZATION_NAME = "2.5.4.10", "organizationName";
+    ORGANIZATIONAL_UNIT_NAME = "2.5.4.11", "organizationalUnitName";
+    TITLE = "2.5.4.12", "title";
+    GIVEN_NAME = "2.5.4.42", "givenName";
+    INITIALS = "2.5.4.43", "initials";
+    PSEUDONYM = "2.5.4.65", "pseudonym";
+    USER_ID = "0.9.2342.19200300.100.1.1", "userId";
+    DOMAIN_COMPONENT = "0.9.2342.19200300.100.1.25", "domainComponent";
+
+    // X.509 extensions
+    SUBJECT_KEY_IDENTIFIER = "2.5.29.14", "subjectKeyIdentifier";
+    KEY_USAGE = "2.5.29.15", "keyUsage";
+    SUBJECT_ALT_NAME = "2.5.29.17", "subjectAltName";
+    ISSUER_ALT_NAME = "2.5.29.18", "issuerAltName";
+    BASIC_CONSTRAINTS = "2.5.29.19", "basicConstraints";
+    CRL_NUMBER = "2.5.29.20", "cRLNumber";
+    CRL_REASON = "2.5.29.21", "cRLReason";
+    NAME_CONSTRAINTS = "2.5.29.30", "nameConstraints";
+    CRL_DISTRIBUTION_POINTS = "2.5.29.31", "cRLDistributionPoints";
+    CERTIFICATE_POLICIES = "2.5.29.32", "certificatePolicies";
+    POLICY_MAPPINGS = "2.5.29.33", "policyMappings";
+    AUTHORITY_KEY_IDENTIFIER = "2.5.29.35", "authorityKeyIdentifier";
+    POLICY_CONSTRAINTS = "2.5.29.36", "policyConstraints";
+    EXT_KEY_USAGE = "2.5.29.37", "extKeyUsage";
+    AUTHORITY_INFO_ACCESS = "1.3.6.1.5.5.7.1.1", "authorityInfoAccess";
+    KP_SERVER_AUTH = "1.3.6.1.5.5.7.3.1", "id-kp-serverAuth";
+    KP_CLIENT_AUTH = "1.3.6.1.5.5.7.3.2", "id-kp-clientAuth";
+    KP_CODE_SIGNING = "1.3.6.1.5.5.7.3.3", "id-kp-codeSigning";
+    KP_EMAIL_PROTECTION = "1.3.6.1.5.5.7.3.4", "id-kp-emailProtection";
+    KP_TIME_STAMPING = "1.3.6.1.5.5.7.3.8", "id-kp-timeStamping";
+    KP_OCSP_SIGNING = "1.3.6.1.5.5.7.3.9", "id-kp-OCSPSigning";
+    AD_OCSP = "1.3.6.1.5.5.7.48.1", "id-ad-ocsp";
+    AD_CA_ISSUERS = "1.3.6.1.5.5.7.48.2", "id-ad-caIssuers";
+
+    // PKCS#5 and PKCS#8 encryption
+    PBKDF2 = "1.2.840.113549.1.5.12", "id-PBKDF2";
+    PBES2 = "1.2.840.113549.1.5.13", "id-PBES2";
+
+    // PKCS#7 and CMS content types
+    DATA = "1.2.840.113549.1.7.1", "id-data";
+    SIGNED_DATA = "1.2.840.113549.1.7.2", "id-signedData";
+    ENVELOPED_DATA = "1.2.840.113549.1.7.3", "id-envelopedData";
+    SIGNED_AND_ENVELOPED_DATA = "1.2.840.113549.1.7.4", "id-signedAndEnvelopedData";
+    DIGESTED_DATA = "1.2.840.113549.1.7.5", "id-digestedData";
+    ENCRYPTED_DATA = "1.2.840.113549.1.7.6", "id-encryptedData";
+    AUTH_DATA = "1.2.840.113549.1.9.16.1.2", "id-ct-authData";
+
+    // PKCS#9 attributes
+    EMAIL_ADDRESS = "1.2.840.113549.1.9.1", "emailAddress";
+    CONTENT_TYPE = "1.2.840.113549.1.9.3", "contentType";
+    MESSAGE_DIGEST = "1.2.840.113549.1.9.4", "messageDigest";
+    SIGNING_TIME = "1.2.840.113549.1.9.5", "signingTime";
+    COUNTERSIGNATURE = "1.2.840.113549.1.9.6", "countersignature";
+    CHALLENGE_PASSWORD = "1.2.840.113549.1.9.7", "challengePassword";
+    EXTENSION_REQUEST = "1.2.840.113549.1.9.14", "extensionRequest";
+    FRIENDLY_NAME = "1.2.840.113549.1.9.20", "friendlyName";
+    LOCAL_KEY_ID = "1.2.840.113549.1.9.21", "localKeyID";
+    X509_CERTIFICATE = "1.2.840.113549.1.9.22.1", "x509Certificate";
+
+    // PKCS#12
+    PBE_WITH_SHA_AND_3_KEY_TRIPLE_DES_CBC = "1.2.840.113549.1.12.1.3", "pbeWithSHAAnd3-KeyTripleDES-CBC";
+    KEY_BAG = "1.2.840.113549.1.12.10.1.1", "keyBag";
+    PKCS8_SHROUDED_KEY_BAG = "1.2.840.113549.1.12.10.1.2", "pkcs8ShroudedKeyBag";
+    CERT_BAG = "1.2.840.113549.1.12.10.1.3", "certBag";
+    CRL_BAG = "1.2.840.113549.1.12.10.1.4", "crlBag";
+    SECRET_BAG = "1.2.840.113549.1.12.10.1.5", "secretBag";
+    SAFE_CONTENTS_BAG = "1.2.840.113549.1.12.10.1.6", "safeContentsBag";
+}
+
+/// Find a well-known OID by its name
+pub fn by_name(name: &str) -> Option<&'static OID> {
+    TABLE.iter().find(|(_, n)| *n == name).map(|(oid, _)| *oid)
+}
+
+impl OID {
+    /// Return the name of the OID if it is a well-known OID
+    pub fn name(&self) -> Option<&'static str> {
+        TABLE.iter().find(|(oid, _)| *oid == self).map(|(_, n)| *n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_lookup() {
+        assert_eq!(COMMON_NAME.as_ref(), &[0x55, 0x04, 0x03]);
+        assert_eq!(COMMON_NAME.name(), Some("commonName"));
+        assert_eq!(
+            by_name("sha256WithRSAEncryption"),
+            Some(SHA256_WITH_RSA_ENCRYPTION)
+        );
+        assert_eq!(by_name("unknown"), None);
+
+        let oid = OID::parse_from_slice(&[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07]).unwrap();
+        assert_eq!(oid.name(), Some("prime256v1"));
+        assert_eq!(crate::oid!("1.2.3.4").name(), None);
+    }
+
+    #[test]
+    fn registry_unique() {
+        for (i, (oid, name)) in TABLE.iter().enumerate() {
+            for (oid2, name2) in &TABLE[i + 1..] {
+                assert_ne!(oid, oid2, "{} duplicated", name);
+                assert_ne!(name, name2);
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use basn1::der::reader::Error;
+use basn1::der::{DerDecode, DerEncode, Reader, Writer};
+use basn1::{BitString, Integer};
+// the derive macros are re-exported along the traits with the derive feature
+#[cfg(not(feature = "derive"))]
+use basn1_derive::{DerDecode, DerEncode};
+
+#[derive(Debug, PartialEq, DerDecode, DerEncode)]
+struct Header<'a> {
+    #[asn1(explicit = 0, default = false)]
+    flag: bool,
+    serial: &'a Integer,
+    #[asn1(implicit = 1, optional)]
+    unique_id: Option<&'a BitString>,
+    #[asn1(explicit = 2, optional)]
+    name: Option<&'a [u8]>,
+}
+
+#[derive(Debug, PartialEq, DerDecode, DerEncode)]
+enum Choice<'a> {
+    Flag(bool),
+    #[asn1(implicit = 0)]
+    Data(&'a [u8]),
+    #[asn1(explicit = 1)]
+    Header(Header<'a>),
+    #[asn1(implicit = 2)]
+    Nothing,
+}
+
+#[derive(Debug, PartialEq, DerDecode, DerEncode)]
+struct Flags(bool, #[asn1(optional)] Option<bool>);
+
+fn roundtrip<T>(value: &T, expected: &[u8])
+where
+    T: DerEncode + for<'a> DerDecode<'a> + PartialEq + std::fmt::Debug,
+{
+    let mut buf = [0u8; 256];
+    let mut writer = Writer::new(&mut buf);
+    writer.encode(value).expect("encode");
+    let slice = writer.finish();
+    assert_eq!(slice, expected);
+
+    let mut reader = Reader::new(slice);
+    let decoded: T = reader.decode().expect("decode");
+    reader.done().expect("done");
+    assert_eq!(&decoded, value);
+}
+
+#[test]
+fn derive_struct() {
+    let data = b"\x30\x03\x02\x01\x05";
+    let mut reader = Reader::new(&data[..]);
+    let header: Header = reader.decode().expect("header");
+    assert!(!header.flag);
+    assert_eq!(header.serial.as_ref(), &[5]);
+    assert_eq!(header.unique_id, None);
+    assert_eq!(header.name, None);
+
+    let mut buf = [0u8; 64];
+    let mut writer = Writer::new(&mut buf);
+    writer.encode(&header).expect("encode");
+    assert_eq!(writer.finish(), &data[..]);
+
+    let data = b"\x30\x14\xa0\x03\x01\x01\xff\x02\x01\x05\x81\x02\x00\xaa\xa2\x06\x04\x04abcd";
+    let mut reader = Reader::new(&data[..]);
+    let header: Header = reader.decode().expect("header");
+    reader.done().expect("done");
+    assert!(header.flag);
+    assert_eq!(header.unique_id.map(|b| b.bits()), Some(8));
+    assert_eq!(header.name, Some(&b"abcd"[..]));
+
+    let mut buf = [0u8; 64];
+    let mut writer = Writer::new(&mut buf);
+    writer.encode(&header).expect("encode");
+    assert_eq!(writer.finish(), &data[..]);
+
+    // the DEFAULT value is never encoded, so an encoded one is invalid
+    let data = b"\x30\x08\xa0\x03\x01\x01\x00\x02\x01\x05";
+    let mut reader = Reader::new(&data[..]);
+    match reader.decode::<Header>() {
+        Err(Error::DefaultValueEncoded { offset: 2 }) => {}
+        r => panic!("unexpected {:?}", r),
+    }
+}
+
+#[derive(Debug, PartialEq, DerDecode, DerEncode)]
+struct Defaulted {
+    #[asn1(default = false)]
+    b: bool,
+    n: (),
+}
+
+#[test]
+fn derive_universal_default() {
+    roundtrip(
+        &Defaulted { b: true, n: () },
+        b"\x30\x05\x01\x01\xff\x05\x00",
+    );
+    roundtrip(&Defaulted { b: false, n: () }, b"\x30\x02\x05\x00");
+    let mut reader = Reader::new(&b"\x30\x05\x01\x01\x00\x05\x00"[..]);
+    match reader.decode::<Defaulted>() {
+        Err(Error::DefaultValueEncoded { offset: 2 }) => {}
+        r => panic!("unexpected {:?}", r),
+    }
+}
+
+#[test]
+fn derive_tuple_struct() {
+    roundtrip(&Flags(true, None), b"\x30\x03\x01\x01\xff");
+    roundtrip(
+        &Flags(true, Some(false)),
+        b"\x30\x06\x01\x01\xff\x01\x01\x00",
+    );
+}
+
+#[test]
+fn derive_choice() {
+    let mut buf = [0u8; 64];
+    for (value, expected) in [
+        (Choice::Flag(true), &b"\x01\x01\xff"[..]),
+        (Choice::Data(b"xy"), &b"\x80\x02xy"[..]),
+        (Choice::Nothing, &b"\x82\x00"[..]),
+    ]
+    .iter()
+    {
+        let mut writer = Writer::new(&mut buf);
+        writer.encode(value).expect("encode");
+        let slice = writer.finish();
+        assert_eq!(slice, *expected);
+        let mut reader = Reader::new(slice);
+        let decoded: Choice = reader.decode().expect("decode");
+        reader.done().expect("done");
+        assert_eq!(&decoded, value);
+    }
+
+    let data = b"\xa1\x05\x30\x03\x02\x01\x07";
+    let mut reader = Reader::new(&data[..]);
+    match reader.decode::<Choice>().expect("decode") {
+        Choice::Header(h) => assert_eq!(h.serial.as_ref(), &[7]),
+        c => panic!("unexpected choice {:?}", c),
+    }
+
+    // no alternative matching
+    let mut reader = Reader::new(&b"\x05\x00"[..]);
+    match reader.decode::<Choice>() {
+        Err(Error::ChoiceInvalid { offset: 0 }) => {}
+        r => panic!("unexpected {:?}", r),
+    }
+
+    // the alternative is chosen by the identifier, and its errors are reported
+    let mut reader = Reader::new(&b"\xa1\x05\x30\x03\x01\x01\x00"[..]);
+    match reader.decode::<Choice>() {
+        Err(Error::ExpectedTag {
+            expected: 2,
+            got: 1,
+        }) => {}
+        r => panic!("unexpected {:?}", r),
+    }
+    assert!(Choice::can_decode(
+        &Reader::new(&b"\x82\x00"[..])
+            .peek_identifier()
+            .unwrap()
+            .unwrap()
+    ));
+}